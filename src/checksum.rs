@@ -0,0 +1,153 @@
+//! オブジェクトの内容の完全性検証用の構成要素。
+use crc32c;
+use sha2::{Digest, Sha256};
+
+use {ErrorKind, Result};
+
+/// チェックサムの計算アルゴリズム。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChecksumAlgorithm {
+    /// CRC32C (Castagnoli)。
+    Crc32c,
+
+    /// SHA-256。
+    Sha256,
+}
+
+/// オブジェクトの内容のチェックサム。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Checksum {
+    /// CRC32C (Castagnoli)。
+    Crc32c(u32),
+
+    /// SHA-256。
+    Sha256([u8; 32]),
+}
+impl Checksum {
+    /// 指定されたアルゴリズムで`data`のチェックサムを計算する。
+    pub fn calculate(algorithm: ChecksumAlgorithm, data: &[u8]) -> Self {
+        let mut hasher = ChecksumHasher::new(algorithm);
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    /// チェックサムの計算アルゴリズムを返す。
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        match *self {
+            Checksum::Crc32c(_) => ChecksumAlgorithm::Crc32c,
+            Checksum::Sha256(_) => ChecksumAlgorithm::Sha256,
+        }
+    }
+
+    /// `data`のチェックサムが、このチェックサムと一致するかどうかを検証する。
+    ///
+    /// 一致しない場合には`ErrorKind::ChecksumMismatch`が返される。
+    pub fn verify(&self, data: &[u8]) -> Result<()> {
+        let actual = Checksum::calculate(self.algorithm(), data);
+        track_assert_eq!(actual, *self, ErrorKind::ChecksumMismatch);
+        Ok(())
+    }
+}
+
+/// チェックサムを逐次的に計算するための構造体。
+#[derive(Debug, Clone)]
+pub struct ChecksumHasher(Inner);
+impl ChecksumHasher {
+    /// 新しい`ChecksumHasher`インスタンスを生成する。
+    pub fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => ChecksumHasher(Inner::Crc32c(0)),
+            ChecksumAlgorithm::Sha256 => ChecksumHasher(Inner::Sha256(Sha256::new())),
+        }
+    }
+
+    /// `data`をチェックサムの計算対象に追加する。
+    pub fn update(&mut self, data: &[u8]) {
+        match self.0 {
+            Inner::Crc32c(ref mut crc) => *crc = crc32c::crc32c_append(*crc, data),
+            Inner::Sha256(ref mut hasher) => hasher.update(data),
+        }
+    }
+
+    /// これまでに追加されたデータのチェックサムを返す。
+    pub fn finish(self) -> Checksum {
+        match self.0 {
+            Inner::Crc32c(crc) => Checksum::Crc32c(crc),
+            Inner::Sha256(hasher) => Checksum::Sha256(hasher.finalize().into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Inner {
+    Crc32c(u32),
+    Sha256(Sha256),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256(hex: &str) -> Checksum {
+        let mut bytes = [0; 32];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        Checksum::Sha256(bytes)
+    }
+
+    #[test]
+    fn crc32c_works() {
+        // NOTE: RFC 3720 (iSCSI) 等で使用されている既知の値
+        assert_eq!(
+            Checksum::calculate(ChecksumAlgorithm::Crc32c, b""),
+            Checksum::Crc32c(0)
+        );
+        assert_eq!(
+            Checksum::calculate(ChecksumAlgorithm::Crc32c, b"123456789"),
+            Checksum::Crc32c(0xe306_9283)
+        );
+        assert_eq!(
+            Checksum::calculate(ChecksumAlgorithm::Crc32c, &[0; 32]),
+            Checksum::Crc32c(0x8a91_36aa)
+        );
+    }
+
+    #[test]
+    fn sha256_works() {
+        // NOTE: FIPS 180-2 の既知の値
+        assert_eq!(
+            Checksum::calculate(ChecksumAlgorithm::Sha256, b""),
+            sha256("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(
+            Checksum::calculate(ChecksumAlgorithm::Sha256, b"abc"),
+            sha256("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    #[test]
+    fn incremental_update_works() {
+        let data = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        for &algorithm in &[ChecksumAlgorithm::Crc32c, ChecksumAlgorithm::Sha256] {
+            let mut hasher = ChecksumHasher::new(algorithm);
+            for chunk in data.chunks(7) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finish(), Checksum::calculate(algorithm, data));
+        }
+        assert_eq!(
+            Checksum::calculate(ChecksumAlgorithm::Sha256, data),
+            sha256("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+        );
+    }
+
+    #[test]
+    fn verify_works() {
+        let checksum = Checksum::Crc32c(0xe306_9283);
+        assert!(checksum.verify(b"123456789").is_ok());
+
+        let e = checksum.verify(b"12345678").err().unwrap();
+        assert_eq!(*e.kind(), ErrorKind::ChecksumMismatch);
+    }
+}
@@ -0,0 +1,166 @@
+//! 構成管理系API用の同期的なRPCクライアント。
+use std::net::SocketAddr;
+
+use super::Runtime;
+use client::config::Client as AsyncClient;
+use client::retry::RetryPolicy;
+use deadline::Deadline;
+use entity::bucket::{Bucket, BucketId, BucketSummary};
+use entity::device::{Device, DeviceId, DeviceSummary};
+use entity::node::RemoteNodeId;
+use entity::server::{Server, ServerId, ServerSummary};
+use Result;
+
+/// `client::config::Client`の同期版。
+///
+/// 各メソッドは、要求の処理が完了するまで呼び出し元のスレッドをブロックする。
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: AsyncClient,
+    runtime: Runtime,
+}
+impl Client {
+    /// 新しい`Client`インスタンスを生成する。
+    ///
+    /// 要求の処理には、新たに生成された`Runtime`が使用される。
+    pub fn new(contact_server: SocketAddr) -> Result<Self> {
+        track!(Self::with_contact_servers(vec![contact_server]))
+    }
+
+    /// 複数の問い合わせ先サーバを指定して、新しい`Client`インスタンスを生成する。
+    ///
+    /// `contact_servers`が空の場合には`ErrorKind::InvalidInput`が返される。
+    pub fn with_contact_servers(contact_servers: Vec<SocketAddr>) -> Result<Self> {
+        let runtime = track!(Runtime::new())?;
+        let inner = track!(AsyncClient::with_contact_servers(
+            contact_servers,
+            runtime.rpc_service().clone()
+        ))?;
+        Ok(Client { inner, runtime })
+    }
+
+    /// 非同期版のクライアントを、`runtime`上で動作する同期版のクライアントに変換する。
+    ///
+    /// `inner`は`runtime.rpc_service()`を使用して生成されている必要がある。
+    pub fn from_async(inner: AsyncClient, runtime: Runtime) -> Self {
+        Client { inner, runtime }
+    }
+
+    /// 再試行ポリシーを変更する。
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.inner = self.inner.with_retry_policy(retry_policy);
+        self
+    }
+
+    /// 再試行ポリシーを返す。
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        self.inner.retry_policy()
+    }
+
+    /// 問い合わせ先サーバの一覧を返す。
+    pub fn contact_servers(&self) -> &[SocketAddr] {
+        self.inner.contact_servers()
+    }
+
+    /// キャッシュされているリーダのアドレスを返す。
+    pub fn leader(&self) -> Option<SocketAddr> {
+        self.inner.leader()
+    }
+
+    /// リーダのキャッシュを破棄する。
+    pub fn invalidate_leader(&self) {
+        self.inner.invalidate_leader()
+    }
+
+    /// 非同期版のクライアントを返す。
+    pub fn as_async(&self) -> &AsyncClient {
+        &self.inner
+    }
+
+    /// 使用している`Runtime`を返す。
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// `ListServersRpc`を実行する。
+    pub fn list_servers(&self, deadline: Deadline) -> Result<Vec<ServerSummary>> {
+        self.runtime.block_on(self.inner.list_servers(deadline))
+    }
+
+    /// `GetServerRpc`を実行する。
+    pub fn get_server(&self, server: ServerId, deadline: Deadline) -> Result<Option<Server>> {
+        self.runtime
+            .block_on(self.inner.get_server(server, deadline))
+    }
+
+    /// `PutServerRpc`を実行する。
+    pub fn put_server(&self, server: Server, deadline: Deadline) -> Result<Server> {
+        self.runtime
+            .block_on(self.inner.put_server(server, deadline))
+    }
+
+    /// `DeleteServerRpc`を実行する。
+    pub fn delete_server(&self, server: ServerId, deadline: Deadline) -> Result<Option<Server>> {
+        self.runtime
+            .block_on(self.inner.delete_server(server, deadline))
+    }
+
+    /// `ListDevicesRpc`を実行する。
+    pub fn list_devices(&self, deadline: Deadline) -> Result<Vec<DeviceSummary>> {
+        self.runtime.block_on(self.inner.list_devices(deadline))
+    }
+
+    /// `GetDeviceRpc`を実行する。
+    pub fn get_device(&self, device: DeviceId, deadline: Deadline) -> Result<Option<Device>> {
+        self.runtime
+            .block_on(self.inner.get_device(device, deadline))
+    }
+
+    /// `PutDeviceRpc`を実行する。
+    pub fn put_device(&self, device: Device, deadline: Deadline) -> Result<Device> {
+        self.runtime
+            .block_on(self.inner.put_device(device, deadline))
+    }
+
+    /// `DeleteDeviceRpc`を実行する。
+    pub fn delete_device(&self, device: DeviceId, deadline: Deadline) -> Result<Option<Device>> {
+        self.runtime
+            .block_on(self.inner.delete_device(device, deadline))
+    }
+
+    /// `ListBucketsRpc`を実行する。
+    pub fn list_buckets(&self, deadline: Deadline) -> Result<Vec<BucketSummary>> {
+        self.runtime.block_on(self.inner.list_buckets(deadline))
+    }
+
+    /// `GetBucketRpc`を実行する。
+    pub fn get_bucket(&self, bucket: BucketId, deadline: Deadline) -> Result<Option<Bucket>> {
+        self.runtime
+            .block_on(self.inner.get_bucket(bucket, deadline))
+    }
+
+    /// `PutBucketRpc`を実行する。
+    pub fn put_bucket(&self, bucket: Bucket, deadline: Deadline) -> Result<Bucket> {
+        self.runtime
+            .block_on(self.inner.put_bucket(bucket, deadline))
+    }
+
+    /// `DeleteBucketRpc`を実行する。
+    pub fn delete_bucket(&self, bucket: BucketId, deadline: Deadline) -> Result<Option<Bucket>> {
+        self.runtime
+            .block_on(self.inner.delete_bucket(bucket, deadline))
+    }
+
+    /// `GetSegmentNodesRpc`を実行する。
+    ///
+    /// 返されるノード一覧の順序は、クラスタ構成上のメンバの順序に従う。
+    pub fn get_segment_nodes(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        deadline: Deadline,
+    ) -> Result<Option<Vec<RemoteNodeId>>> {
+        self.runtime
+            .block_on(self.inner.get_segment_nodes(bucket_id, segment, deadline))
+    }
+}
@@ -0,0 +1,789 @@
+//! Frugalosの公開API用の同期的なRPCクライアント。
+use futures::Stream;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::time::Duration;
+
+use super::config::Client as ConfigClient;
+use super::{Iter, Runtime};
+use client::breaker::CircuitBreaker;
+use client::frugalos::{Client as AsyncClient, JobHandle, PutObjectOptions};
+use client::pool::{Balancing, ServerStatus};
+use client::retry::RetryPolicy;
+use consistency::ReadConsistency;
+use deadline::Deadline;
+use entity::bucket::BucketId;
+use entity::device::DeviceId;
+use entity::object::{
+    AttributeFilter, DeleteObjectsByPrefixProgress, DeleteObjectsByPrefixSummary, FragmentsSummary,
+    ObjectChunk, ObjectId, ObjectInfo, ObjectPrefix, ObjectSummary, ObjectSummaryPage,
+    ObjectVersion,
+};
+use expect::Expect;
+use multiplicity::MultiplicityConfig;
+use repair::RepairConfig;
+use schema::frugalos::{BucketEvents, ObjectItem, ObjectTarget, PutObjectItem};
+use time::UnixTimestamp;
+use {Error, Result};
+
+/// `client::frugalos::Client`の同期版。
+///
+/// 各メソッドは、要求の処理が完了するまで呼び出し元のスレッドをブロックする。
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: AsyncClient,
+    runtime: Runtime,
+}
+impl Client {
+    /// 新しい`Client`インスタンスを生成する。
+    ///
+    /// 要求の処理には、新たに生成された`Runtime`が使用される。
+    pub fn new(server: SocketAddr) -> Result<Self> {
+        track!(Self::with_servers(vec![server]))
+    }
+
+    /// 複数のサーバを指定して、新しい`Client`インスタンスを生成する。
+    ///
+    /// `servers`が空の場合には`ErrorKind::InvalidInput`が返される。
+    pub fn with_servers(servers: Vec<SocketAddr>) -> Result<Self> {
+        let runtime = track!(Runtime::new())?;
+        let inner = track!(AsyncClient::with_servers(
+            servers,
+            runtime.rpc_service().clone()
+        ))?;
+        Ok(Client { inner, runtime })
+    }
+
+    /// クラスタに登録されている全てのサーバを`config`経由で取得し、新しい`Client`インスタンスを生成する。
+    ///
+    /// 生成されたインスタンスは、`config`と同じ`Runtime`を使用する。
+    pub fn discover(config: &ConfigClient, deadline: Deadline) -> Result<Self> {
+        let runtime = config.runtime().clone();
+        let future =
+            AsyncClient::discover(config.as_async(), runtime.rpc_service().clone(), deadline);
+        let inner = track!(runtime.block_on(future))?;
+        Ok(Client { inner, runtime })
+    }
+
+    /// 非同期版のクライアントを、`runtime`上で動作する同期版のクライアントに変換する。
+    ///
+    /// `inner`は`runtime.rpc_service()`を使用して生成されている必要がある。
+    pub fn from_async(inner: AsyncClient, runtime: Runtime) -> Self {
+        Client { inner, runtime }
+    }
+
+    /// 要求の振り分け方式を変更する。
+    pub fn with_balancing(mut self, balancing: Balancing) -> Self {
+        self.inner = self.inner.with_balancing(balancing);
+        self
+    }
+
+    /// 要求の振り分け方式を返す。
+    pub fn balancing(&self) -> Balancing {
+        self.inner.balancing()
+    }
+
+    /// 各サーバの状態を返す。
+    pub fn server_statuses(&self) -> Vec<ServerStatus> {
+        self.inner.server_statuses()
+    }
+
+    /// ヘッジングを有効にする。
+    ///
+    /// 詳細は`client::frugalos::Client::with_hedging`を参照のこと。
+    pub fn with_hedging(mut self, delay: Duration) -> Self {
+        self.inner = self.inner.with_hedging(delay);
+        self
+    }
+
+    /// ヘッジングの待ち時間を返す。
+    pub fn hedging_delay(&self) -> Option<Duration> {
+        self.inner.hedging_delay()
+    }
+
+    /// サーキットブレーカを設定する。
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.inner = self.inner.with_circuit_breaker(breaker);
+        self
+    }
+
+    /// 再試行ポリシーを変更する。
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.inner = self.inner.with_retry_policy(retry_policy);
+        self
+    }
+
+    /// 再試行ポリシーを返す。
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        self.inner.retry_policy()
+    }
+
+    /// 非同期版のクライアントを返す。
+    pub fn as_async(&self) -> &AsyncClient {
+        &self.inner
+    }
+
+    /// 使用している`Runtime`を返す。
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// `GetObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、応答の遅いサーバに加えて別のサーバにも要求が送信される。
+    ///
+    /// オブジェクトにチェックサムが付与されている場合には、取得した内容を検証する。
+    /// 一致しない場合には`ErrorKind::ChecksumMismatch`が返される。
+    pub fn get_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        deadline: Deadline,
+        expect: Expect,
+        consistency: ReadConsistency,
+    ) -> Result<Option<(ObjectInfo, Vec<u8>)>> {
+        self.runtime.block_on(self.inner.get_object(
+            bucket_id,
+            object_id,
+            deadline,
+            expect,
+            consistency,
+        ))
+    }
+
+    /// `GetObjectRangeRpc`を実行する。
+    ///
+    /// 返される断片には、指定範囲のデータに加えて、オブジェクト全体のサイズとバージョンが含まれる。
+    pub fn get_object_range(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        range: Range<u64>,
+        deadline: Deadline,
+        expect: Expect,
+        consistency: ReadConsistency,
+    ) -> Result<Option<ObjectChunk>> {
+        self.runtime.block_on(self.inner.get_object_range(
+            bucket_id,
+            object_id,
+            range,
+            deadline,
+            expect,
+            consistency,
+        ))
+    }
+
+    /// `GetObjectChunkRpc`を繰り返し実行して、オブジェクトの内容を断片単位で取得する。
+    ///
+    /// 各断片は最大で`chunk_size`バイトとなるため、メモリ使用量はオブジェクトのサイズに依存しない。
+    /// 二番目以降の断片は、最初の断片と同じバージョンのオブジェクトから取得される。
+    ///
+    /// オブジェクトが存在しない場合には、要素を一つも返さずにイテレータが終了する。
+    /// 空のオブジェクトの場合には、データが空の断片が一つだけ返される。
+    ///
+    /// オブジェクトにチェックサムが付与されている場合には、受信した断片から逐次的に計算して検証する。
+    /// 一致しない場合には、最後の断片の代わりに`ErrorKind::ChecksumMismatch`が返される。
+    ///
+    /// `chunk_timeout`は、断片毎の要求(再送や再試行を含む)に個別に適用される。
+    pub fn get_object_stream(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        chunk_timeout: Duration,
+        expect: Expect,
+        consistency: ReadConsistency,
+        chunk_size: u32,
+    ) -> Iter<ObjectChunk> {
+        self.runtime.iter(self.inner.get_object_stream(
+            bucket_id,
+            object_id,
+            chunk_timeout,
+            expect,
+            consistency,
+            chunk_size,
+        ))
+    }
+
+    /// `ListObjectsRpc`を実行する。
+    pub fn list_objects(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<Vec<ObjectSummary>> {
+        self.runtime.block_on(
+            self.inner
+                .list_objects(bucket_id, segment, consistency, deadline),
+        )
+    }
+
+    /// バケツ内の全てのセグメントに対して`ListObjectsRpc`を実行し、結果をまとめて返す。
+    ///
+    /// バケツのセグメント数は`config`経由で取得される。
+    /// 同時に問い合わせるセグメントの数は最大で`concurrency`となる。
+    /// セグメント間でのオブジェクトの順序は保証されない。
+    pub fn list_all_objects(
+        &self,
+        config: &ConfigClient,
+        bucket_id: BucketId,
+        consistency: ReadConsistency,
+        concurrency: usize,
+        deadline: Deadline,
+    ) -> Iter<ObjectSummary> {
+        self.runtime.iter(self.inner.list_all_objects(
+            config.as_async(),
+            bucket_id,
+            consistency,
+            concurrency,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsPageRpc`を実行する。
+    pub fn list_objects_page(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        consistency: ReadConsistency,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Result<ObjectSummaryPage> {
+        self.runtime.block_on(self.inner.list_objects_page(
+            bucket_id,
+            segment,
+            consistency,
+            start_after,
+            max_count,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsPageRpc`を繰り返し実行して、セグメント内のオブジェクト一覧をページ単位で返す。
+    pub fn list_objects_pages(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Iter<Vec<ObjectSummary>> {
+        self.runtime.iter(self.inner.list_objects_pages(
+            bucket_id,
+            segment,
+            consistency,
+            max_count,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsByPrefixRpc`を実行する。
+    pub fn list_objects_by_prefix(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> Result<Vec<ObjectSummary>> {
+        self.runtime.block_on(
+            self.inner
+                .list_objects_by_prefix(bucket_id, prefix, deadline),
+        )
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を実行する。
+    pub fn list_objects_by_prefix_page(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+    ) -> Result<ObjectSummaryPage> {
+        self.runtime
+            .block_on(self.inner.list_objects_by_prefix_page(
+                bucket_id,
+                prefix,
+                deadline,
+                start_after,
+                max_count,
+            ))
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を繰り返し実行して、
+    /// 接頭辞に一致するオブジェクトの一覧をページ単位で返す。
+    pub fn list_objects_by_prefix_pages(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+        max_count: u32,
+    ) -> Iter<Vec<ObjectSummary>> {
+        self.runtime.iter(
+            self.inner
+                .list_objects_by_prefix_pages(bucket_id, prefix, deadline, max_count),
+        )
+    }
+
+    /// `ListExpiringObjectsRpc`を実行する。
+    pub fn list_expiring_objects(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        before: UnixTimestamp,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<Vec<ObjectSummary>> {
+        self.runtime.block_on(self.inner.list_expiring_objects(
+            bucket_id,
+            segment,
+            before,
+            consistency,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsByAttributeRpc`を実行する。
+    pub fn list_objects_by_attribute(
+        &self,
+        bucket_id: BucketId,
+        filter: AttributeFilter,
+        deadline: Deadline,
+    ) -> Result<Vec<ObjectSummary>> {
+        self.runtime.block_on(
+            self.inner
+                .list_objects_by_attribute(bucket_id, filter, deadline),
+        )
+    }
+
+    /// `GetLatestVersionRpc`を実行する。
+    pub fn latest_version(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        deadline: Deadline,
+    ) -> Result<Option<ObjectSummary>> {
+        self.runtime
+            .block_on(self.inner.latest_version(bucket_id, segment, deadline))
+    }
+
+    /// `WatchObjectsRpc`を実行する。
+    pub fn watch_objects(
+        &self,
+        bucket_id: BucketId,
+        since: BTreeMap<u16, ObjectVersion>,
+        max_count: u32,
+        timeout: Duration,
+        deadline: Deadline,
+    ) -> Result<BucketEvents> {
+        self.runtime.block_on(
+            self.inner
+                .watch_objects(bucket_id, since, max_count, timeout, deadline),
+        )
+    }
+
+    /// `CountFragmentsRpc`を実行する。
+    pub fn count_fragments(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        deadline: Deadline,
+        expect: Expect,
+        consistency: ReadConsistency,
+    ) -> Result<Option<FragmentsSummary>> {
+        self.runtime.block_on(self.inner.count_fragments(
+            bucket_id,
+            object_id,
+            deadline,
+            expect,
+            consistency,
+        ))
+    }
+
+    /// `HeadObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、応答の遅いサーバに加えて別のサーバにも要求が送信される。
+    pub fn head_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        deadline: Deadline,
+        expect: Expect,
+        consistency: ReadConsistency,
+        check_storage: bool,
+    ) -> Result<Option<ObjectInfo>> {
+        self.runtime.block_on(self.inner.head_object(
+            bucket_id,
+            object_id,
+            deadline,
+            expect,
+            consistency,
+            check_storage,
+        ))
+    }
+
+    /// `PutObjectRpc`を実行する。
+    pub fn put_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        content: Vec<u8>,
+        deadline: Deadline,
+        expect: Expect,
+        multiplicity_config: MultiplicityConfig,
+    ) -> Result<(ObjectVersion, bool)> {
+        self.runtime.block_on(self.inner.put_object(
+            bucket_id,
+            object_id,
+            content,
+            deadline,
+            expect,
+            multiplicity_config,
+        ))
+    }
+
+    /// `PutObjectWithOptionsRpc`を実行する。
+    pub fn put_object_with_options(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        content: Vec<u8>,
+        deadline: Deadline,
+        expect: Expect,
+        options: PutObjectOptions,
+    ) -> Result<(ObjectVersion, bool)> {
+        self.runtime.block_on(
+            self.inner
+                .put_object_with_options(bucket_id, object_id, content, deadline, expect, options),
+        )
+    }
+
+    /// 分割アップロード用のRPC群を実行して、`content`の内容をオブジェクトとして保存する。
+    ///
+    /// `content`が返すバイト列が、そのまま一つの断片としてサーバに送信される。
+    /// ファイル等の内容を保存する場合には`ChunkReader`を使用すること。
+    ///
+    /// `chunk_timeout`は、アップロードの開始・完了および断片毎の要求に個別に適用される。
+    ///
+    /// 途中でエラーが発生した場合には、アップロードは中断される。
+    pub fn put_object_stream<S>(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        content: S,
+        chunk_timeout: Duration,
+        expect: Expect,
+        options: PutObjectOptions,
+    ) -> Result<(ObjectVersion, bool)>
+    where
+        S: Stream<Item = Vec<u8>, Error = Error> + Send + 'static,
+    {
+        self.runtime.block_on(self.inner.put_object_stream(
+            bucket_id,
+            object_id,
+            content,
+            chunk_timeout,
+            expect,
+            options,
+        ))
+    }
+
+    /// `DeleteObjectRpc`を実行する。
+    pub fn delete_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        deadline: Deadline,
+        expect: Expect,
+    ) -> Result<Option<ObjectVersion>> {
+        self.runtime.block_on(
+            self.inner
+                .delete_object(bucket_id, object_id, deadline, expect),
+        )
+    }
+
+    /// `BatchPutObjectsRpc`を実行する。
+    ///
+    /// 結果は`objects`と同じ順序で、オブジェクト毎に返される。
+    pub fn batch_put_objects(
+        &self,
+        bucket_id: BucketId,
+        objects: Vec<PutObjectItem>,
+        deadline: Deadline,
+        multiplicity_config: MultiplicityConfig,
+    ) -> Result<Vec<Result<(ObjectVersion, bool)>>> {
+        self.runtime.block_on(self.inner.batch_put_objects(
+            bucket_id,
+            objects,
+            deadline,
+            multiplicity_config,
+        ))
+    }
+
+    /// `BatchGetObjectsRpc`を実行する。
+    ///
+    /// 結果は`objects`と同じ順序で、オブジェクト毎に返される。
+    /// チェックサムが一致しないオブジェクトの結果は`ErrorKind::ChecksumMismatch`となる。
+    #[allow(clippy::type_complexity)]
+    pub fn batch_get_objects(
+        &self,
+        bucket_id: BucketId,
+        objects: Vec<ObjectItem>,
+        deadline: Deadline,
+        consistency: ReadConsistency,
+    ) -> Result<Vec<Result<Option<(ObjectInfo, Vec<u8>)>>>> {
+        self.runtime.block_on(self.inner.batch_get_objects(
+            bucket_id,
+            objects,
+            deadline,
+            consistency,
+        ))
+    }
+
+    /// `BatchDeleteObjectsRpc`を実行する。
+    ///
+    /// 結果は`objects`と同じ順序で、オブジェクト毎に返される。
+    pub fn batch_delete_objects(
+        &self,
+        bucket_id: BucketId,
+        objects: Vec<ObjectItem>,
+        deadline: Deadline,
+    ) -> Result<Vec<Result<Option<ObjectVersion>>>> {
+        self.runtime.block_on(
+            self.inner
+                .batch_delete_objects(bucket_id, objects, deadline),
+        )
+    }
+
+    /// `CopyObjectRpc`を実行する。
+    pub fn copy_object(
+        &self,
+        source: ObjectTarget,
+        destination: ObjectTarget,
+        deadline: Deadline,
+        multiplicity_config: MultiplicityConfig,
+    ) -> Result<Option<(ObjectVersion, bool)>> {
+        self.runtime.block_on(self.inner.copy_object(
+            source,
+            destination,
+            deadline,
+            multiplicity_config,
+        ))
+    }
+
+    /// `RenameObjectRpc`を実行する。
+    pub fn rename_object(
+        &self,
+        source: ObjectTarget,
+        destination: ObjectTarget,
+        deadline: Deadline,
+        multiplicity_config: MultiplicityConfig,
+    ) -> Result<Option<(ObjectVersion, bool)>> {
+        self.runtime.block_on(self.inner.rename_object(
+            source,
+            destination,
+            deadline,
+            multiplicity_config,
+        ))
+    }
+
+    /// `DeleteObjectByVersionRpc`を実行する。
+    pub fn delete_object_by_version(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        object_version: ObjectVersion,
+        deadline: Deadline,
+    ) -> Result<Option<ObjectVersion>> {
+        self.runtime.block_on(self.inner.delete_object_by_version(
+            bucket_id,
+            segment,
+            object_version,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsByRangeRpc`を実行する。
+    pub fn list_objects_by_range(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Result<Vec<ObjectSummary>> {
+        self.runtime.block_on(self.inner.list_objects_by_range(
+            bucket_id,
+            segment,
+            targets,
+            consistency,
+            max_count,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsByRangeRpc`を繰り返し実行して、
+    /// 指定範囲のバージョンを持つオブジェクトの一覧を、バージョンの昇順にページ単位で返す。
+    pub fn list_objects_by_range_pages(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Iter<Vec<ObjectSummary>> {
+        self.runtime.iter(self.inner.list_objects_by_range_pages(
+            bucket_id,
+            segment,
+            targets,
+            consistency,
+            max_count,
+            deadline,
+        ))
+    }
+
+    /// `DeleteObjectsByRangeRpc`を実行する。
+    pub fn delete_by_range(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        targets: Range<ObjectVersion>,
+        deadline: Deadline,
+    ) -> Result<Vec<ObjectSummary>> {
+        self.runtime.block_on(
+            self.inner
+                .delete_by_range(bucket_id, segment, targets, deadline),
+        )
+    }
+
+    /// オブジェクトを ID のプレフィックスを指定して削除する。
+    pub fn delete_by_prefix(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> Result<DeleteObjectsByPrefixSummary> {
+        self.runtime
+            .block_on(self.inner.delete_by_prefix(bucket_id, prefix, deadline))
+    }
+
+    /// `StartDeleteObjectsByPrefixRpc`を実行する。
+    ///
+    /// 削除処理の完了を待たずにジョブのハンドルを返す。
+    /// 進捗は`delete_by_prefix_progress`で取得できる。
+    ///
+    /// ハンドルを指定した以降の要求は、常にジョブを開始したサーバに送信される。
+    pub fn start_delete_by_prefix(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> Result<JobHandle> {
+        self.runtime.block_on(
+            self.inner
+                .start_delete_by_prefix(bucket_id, prefix, deadline),
+        )
+    }
+
+    /// `GetDeleteObjectsByPrefixProgressRpc`を、ジョブを開始したサーバに対して実行する。
+    pub fn delete_by_prefix_progress(
+        &self,
+        job: JobHandle,
+        deadline: Deadline,
+    ) -> Result<Option<DeleteObjectsByPrefixProgress>> {
+        self.runtime
+            .block_on(self.inner.delete_by_prefix_progress(job, deadline))
+    }
+
+    /// `CancelDeleteObjectsByPrefixRpc`を、ジョブを開始したサーバに対して実行する。
+    pub fn cancel_delete_by_prefix(
+        &self,
+        job: JobHandle,
+        deadline: Deadline,
+    ) -> Result<Option<DeleteObjectsByPrefixProgress>> {
+        self.runtime
+            .block_on(self.inner.cancel_delete_by_prefix(job, deadline))
+    }
+
+    /// `PreviewDeleteObjectsByPrefixRpc`を実行する。
+    ///
+    /// `delete_by_prefix`で削除されるオブジェクトの数を返す。オブジェクトは削除されない。
+    pub fn preview_delete_by_prefix(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> Result<DeleteObjectsByPrefixSummary> {
+        self.runtime.block_on(
+            self.inner
+                .preview_delete_by_prefix(bucket_id, prefix, deadline),
+        )
+    }
+
+    /// `PreviewDeleteObjectsByRangeRpc`を実行する。
+    ///
+    /// `delete_by_range`で削除されるオブジェクトの一覧を返す。オブジェクトは削除されない。
+    pub fn preview_delete_by_range(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        targets: Range<ObjectVersion>,
+        deadline: Deadline,
+    ) -> Result<Vec<ObjectSummary>> {
+        self.runtime.block_on(
+            self.inner
+                .preview_delete_by_range(bucket_id, segment, targets, deadline),
+        )
+    }
+
+    /// Executes `DeleteObjectSetFromDeviceRpc`.
+    pub fn delete_from_device_by_object_ids(
+        &self,
+        bucket_id: BucketId,
+        device_id: DeviceId,
+        object_ids: BTreeSet<ObjectId>,
+        deadline: Deadline,
+    ) -> Result<()> {
+        self.runtime.block_on(
+            self.inner
+                .delete_from_device_by_object_ids(bucket_id, device_id, object_ids, deadline),
+        )
+    }
+
+    /// Executes `PreviewDeleteObjectSetFromDeviceRpc`.
+    ///
+    /// Returns the objects that `delete_from_device_by_object_ids` would delete.
+    /// Nothing is deleted.
+    pub fn preview_delete_from_device_by_object_ids(
+        &self,
+        bucket_id: BucketId,
+        device_id: DeviceId,
+        object_ids: BTreeSet<ObjectId>,
+        deadline: Deadline,
+    ) -> Result<BTreeSet<ObjectId>> {
+        self.runtime.block_on(
+            self.inner.preview_delete_from_device_by_object_ids(
+                bucket_id, device_id, object_ids, deadline,
+            ),
+        )
+    }
+
+    /// `StopRpc`を実行する。
+    pub fn stop(&self, deadline: Deadline) -> Result<()> {
+        self.runtime.block_on(self.inner.stop(deadline))
+    }
+
+    /// `TakeSnapshotRpc`を実行する。
+    pub fn take_snapshot(&self, deadline: Deadline) -> Result<()> {
+        self.runtime.block_on(self.inner.take_snapshot(deadline))
+    }
+
+    /// Executes `SetRepairConfigRpc`
+    pub fn set_repair_config(&self, repair_config: RepairConfig, deadline: Deadline) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.set_repair_config(repair_config, deadline))
+    }
+}
@@ -0,0 +1,462 @@
+//! MDS(metadata store)用の同期的なRPCクライアント。
+use std::ops::Range;
+use std::time::Duration;
+
+use super::{Iter, Runtime};
+use client::breaker::CircuitBreaker;
+use client::mds::{Client as AsyncClient, PutObjectOptions};
+use client::retry::RetryPolicy;
+use consistency::ReadConsistency;
+use deadline::Deadline;
+use entity::node::RemoteNodeId;
+use entity::object::{
+    AttributeFilter, DeleteObjectsByPrefixSummary, Metadata, ObjectEvent, ObjectEvents, ObjectId,
+    ObjectInfo, ObjectPrefix, ObjectSummary, ObjectSummaryPage, ObjectVersion,
+};
+use expect::Expect;
+use schema::mds::{TransactionOperation, TransactionOutcome};
+use time::UnixTimestamp;
+use Result;
+
+/// `client::mds::Client`の同期版。
+///
+/// 各メソッドは、要求の処理が完了するまで呼び出し元のスレッドをブロックする。
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: AsyncClient,
+    runtime: Runtime,
+}
+impl Client {
+    /// 新しい`Client`インスタンスを生成する。
+    ///
+    /// 要求の処理には、新たに生成された`Runtime`が使用される。
+    pub fn new(node: RemoteNodeId) -> Result<Self> {
+        let runtime = track!(Runtime::new())?;
+        let inner = AsyncClient::new(node, runtime.rpc_service().clone());
+        Ok(Client { inner, runtime })
+    }
+
+    /// 非同期版のクライアントを、`runtime`上で動作する同期版のクライアントに変換する。
+    ///
+    /// `inner`は`runtime.rpc_service()`を使用して生成されている必要がある。
+    pub fn from_async(inner: AsyncClient, runtime: Runtime) -> Self {
+        Client { inner, runtime }
+    }
+
+    /// 再試行ポリシーを変更する。
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.inner = self.inner.with_retry_policy(retry_policy);
+        self
+    }
+
+    /// 再試行ポリシーを返す。
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        self.inner.retry_policy()
+    }
+
+    /// ヘッジングを有効にする。
+    ///
+    /// 詳細は`client::mds::Client::with_hedging`を参照のこと。
+    pub fn with_hedging(mut self, delay: Duration, peers: Vec<RemoteNodeId>) -> Self {
+        self.inner = self.inner.with_hedging(delay, peers);
+        self
+    }
+
+    /// サーキットブレーカを設定する。
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.inner = self.inner.with_circuit_breaker(breaker);
+        self
+    }
+
+    /// キャッシュされているリーダノードを返す。
+    pub fn leader(&self) -> Option<RemoteNodeId> {
+        self.inner.leader()
+    }
+
+    /// リーダノードのキャッシュを更新する。
+    pub fn set_leader(&self, leader: RemoteNodeId) {
+        self.inner.set_leader(leader)
+    }
+
+    /// リーダノードのキャッシュを破棄する。
+    pub fn invalidate_leader(&self) {
+        self.inner.invalidate_leader()
+    }
+
+    /// 非同期版のクライアントを返す。
+    pub fn as_async(&self) -> &AsyncClient {
+        &self.inner
+    }
+
+    /// 使用している`Runtime`を返す。
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// `RecommendToLeaderRpc`を実行する。
+    pub fn recommend_to_leader(&self) {
+        self.inner.recommend_to_leader()
+    }
+
+    /// `ListObjectsRpc`を実行する。
+    pub fn list_objects(
+        &self,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<ObjectSummary>)> {
+        self.runtime
+            .block_on(self.inner.list_objects(consistency, deadline))
+    }
+
+    /// `ListObjectsPageRpc`を実行する。
+    pub fn list_objects_page(
+        &self,
+        consistency: ReadConsistency,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, ObjectSummaryPage)> {
+        self.runtime.block_on(self.inner.list_objects_page(
+            consistency,
+            start_after,
+            max_count,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsPageRpc`を繰り返し実行して、オブジェクト一覧をページ単位で返す。
+    pub fn list_objects_pages(
+        &self,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Iter<Vec<ObjectSummary>> {
+        self.runtime.iter(
+            self.inner
+                .list_objects_pages(consistency, max_count, deadline),
+        )
+    }
+
+    /// `ListObjectsByPrefixRpc`を実行する。
+    pub fn list_objects_by_prefix(
+        &self,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<ObjectSummary>)> {
+        self.runtime
+            .block_on(self.inner.list_objects_by_prefix(prefix, deadline))
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を実行する。
+    pub fn list_objects_by_prefix_page(
+        &self,
+        prefix: ObjectPrefix,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, ObjectSummaryPage)> {
+        self.runtime
+            .block_on(self.inner.list_objects_by_prefix_page(
+                prefix,
+                start_after,
+                max_count,
+                deadline,
+            ))
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を繰り返し実行して、
+    /// 接頭辞に一致するオブジェクトの一覧をページ単位で返す。
+    pub fn list_objects_by_prefix_pages(
+        &self,
+        prefix: ObjectPrefix,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Iter<Vec<ObjectSummary>> {
+        self.runtime.iter(
+            self.inner
+                .list_objects_by_prefix_pages(prefix, max_count, deadline),
+        )
+    }
+
+    /// `WatchObjectsRpc`を実行する。
+    pub fn watch_objects(
+        &self,
+        since: ObjectVersion,
+        max_count: u32,
+        timeout: Duration,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, ObjectEvents)> {
+        self.runtime.block_on(
+            self.inner
+                .watch_objects(since, max_count, timeout, deadline),
+        )
+    }
+
+    /// `WatchObjectsRpc`を繰り返し実行して、`since`より後に発生した変更通知を順に返す。
+    ///
+    /// ノードとの通信に失敗した場合(i.e., `ErrorKind::Unavailable`や`ErrorKind::Timeout`)には、
+    /// 最後に受信した変更の直後から取得を再開する。
+    ///
+    /// 終端のないイテレータなのでデッドラインは指定しない。
+    /// 各要求のデッドラインは`timeout`に一定の猶予を加えたものとなる。
+    pub fn watch(
+        &self,
+        since: ObjectVersion,
+        max_count: u32,
+        timeout: Duration,
+    ) -> Iter<ObjectEvent> {
+        self.runtime
+            .iter(self.inner.watch(since, max_count, timeout))
+    }
+
+    /// `ListObjectsByAttributeRpc`を実行する。
+    pub fn list_objects_by_attribute(
+        &self,
+        filter: AttributeFilter,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<ObjectSummary>)> {
+        self.runtime
+            .block_on(self.inner.list_objects_by_attribute(filter, deadline))
+    }
+
+    /// `GetLatestVersionRpc`を実行する。
+    pub fn latest_version(
+        &self,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Option<ObjectSummary>)> {
+        self.runtime.block_on(self.inner.latest_version(deadline))
+    }
+
+    /// セグメントが保持しているオブジェクトの数を返す.
+    pub fn object_count(
+        &self,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, u64)> {
+        self.runtime
+            .block_on(self.inner.object_count(consistency, deadline))
+    }
+
+    /// `GetObjectRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
+    pub fn get_object(
+        &self,
+        id: ObjectId,
+        expect: Expect,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Option<Metadata>)> {
+        self.runtime
+            .block_on(self.inner.get_object(id, expect, consistency, deadline))
+    }
+
+    /// `HeadObjectRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
+    pub fn head_object(
+        &self,
+        id: ObjectId,
+        expect: Expect,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Option<ObjectVersion>)> {
+        self.runtime
+            .block_on(self.inner.head_object(id, expect, consistency, deadline))
+    }
+
+    /// `GetObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
+    #[allow(clippy::type_complexity)]
+    pub fn get_object_with_info(
+        &self,
+        id: ObjectId,
+        expect: Expect,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Option<(ObjectInfo, Vec<u8>)>)> {
+        self.runtime.block_on(
+            self.inner
+                .get_object_with_info(id, expect, consistency, deadline),
+        )
+    }
+
+    /// `HeadObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
+    pub fn head_object_with_info(
+        &self,
+        id: ObjectId,
+        expect: Expect,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Option<ObjectInfo>)> {
+        self.runtime.block_on(
+            self.inner
+                .head_object_with_info(id, expect, consistency, deadline),
+        )
+    }
+
+    /// `PutObjectRpc`を実行する。
+    #[allow(clippy::type_complexity)]
+    pub fn put_object(
+        &self,
+        id: ObjectId,
+        metadata: Vec<u8>,
+        expect: Expect,
+        put_content_timeout: Duration,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, (ObjectVersion, Option<ObjectVersion>))> {
+        self.runtime.block_on(self.inner.put_object(
+            id,
+            metadata,
+            expect,
+            put_content_timeout,
+            deadline,
+        ))
+    }
+
+    /// `PutObjectWithOptionsRpc`を実行する。
+    #[allow(clippy::type_complexity)]
+    pub fn put_object_with_options(
+        &self,
+        id: ObjectId,
+        metadata: Vec<u8>,
+        expect: Expect,
+        put_content_timeout: Duration,
+        deadline: Deadline,
+        options: PutObjectOptions,
+    ) -> Result<(Option<RemoteNodeId>, (ObjectVersion, Option<ObjectVersion>))> {
+        self.runtime.block_on(self.inner.put_object_with_options(
+            id,
+            metadata,
+            expect,
+            put_content_timeout,
+            deadline,
+            options,
+        ))
+    }
+
+    /// `ListExpiringObjectsRpc`を実行する。
+    pub fn list_expiring_objects(
+        &self,
+        before: UnixTimestamp,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<ObjectSummary>)> {
+        self.runtime.block_on(
+            self.inner
+                .list_expiring_objects(before, consistency, deadline),
+        )
+    }
+
+    /// `DeleteObjectRpc`を実行する。
+    pub fn delete_object(
+        &self,
+        id: ObjectId,
+        expect: Expect,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Option<ObjectVersion>)> {
+        self.runtime
+            .block_on(self.inner.delete_object(id, expect, deadline))
+    }
+
+    /// `TransactionRpc`を実行する。
+    ///
+    /// 結果は`operations`と同じ順序で返される。
+    pub fn transaction(
+        &self,
+        operations: Vec<TransactionOperation>,
+        put_content_timeout: Duration,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<TransactionOutcome>)> {
+        self.runtime.block_on(
+            self.inner
+                .transaction(operations, put_content_timeout, deadline),
+        )
+    }
+
+    /// `DeleteObjectByVersionRpc`を実行する。
+    pub fn delete_object_by_version(
+        &self,
+        version: ObjectVersion,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Option<ObjectVersion>)> {
+        self.runtime
+            .block_on(self.inner.delete_object_by_version(version, deadline))
+    }
+
+    /// `ListObjectsByRangeRpc`を実行する。
+    pub fn list_objects_by_range(
+        &self,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<ObjectSummary>)> {
+        self.runtime.block_on(self.inner.list_objects_by_range(
+            targets,
+            consistency,
+            max_count,
+            deadline,
+        ))
+    }
+
+    /// `ListObjectsByRangeRpc`を繰り返し実行して、
+    /// 指定範囲のバージョンを持つオブジェクトの一覧を、バージョンの昇順にページ単位で返す。
+    pub fn list_objects_by_range_pages(
+        &self,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> Iter<Vec<ObjectSummary>> {
+        self.runtime.iter(self.inner.list_objects_by_range_pages(
+            targets,
+            consistency,
+            max_count,
+            deadline,
+        ))
+    }
+
+    /// `DeleteObjectsByRangeRpc`を実行する。
+    pub fn delete_by_range(
+        &self,
+        targets: Range<ObjectVersion>,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<ObjectSummary>)> {
+        self.runtime
+            .block_on(self.inner.delete_by_range(targets, deadline))
+    }
+
+    /// `DeleteObjectsByPrefixRpc`を実行する。
+    pub fn delete_by_prefix(
+        &self,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, DeleteObjectsByPrefixSummary)> {
+        self.runtime
+            .block_on(self.inner.delete_by_prefix(prefix, deadline))
+    }
+
+    /// `PreviewDeleteObjectsByRangeRpc`を実行する。
+    pub fn preview_delete_by_range(
+        &self,
+        targets: Range<ObjectVersion>,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, Vec<ObjectSummary>)> {
+        self.runtime
+            .block_on(self.inner.preview_delete_by_range(targets, deadline))
+    }
+
+    /// `PreviewDeleteObjectsByPrefixRpc`を実行する。
+    pub fn preview_delete_by_prefix(
+        &self,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> Result<(Option<RemoteNodeId>, DeleteObjectsByPrefixSummary)> {
+        self.runtime
+            .block_on(self.inner.preview_delete_by_prefix(prefix, deadline))
+    }
+}
@@ -0,0 +1,186 @@
+//! 同期的なRPCクライアント。
+//!
+//! 各クライアントは専用のエクゼキュータとRPCサービスを内部に保持しているため、
+//! `fibers`のエクゼキュータを用意することなく、通常のスレッドから利用できる。
+//! 保守用のツールやスクリプトなど、非同期処理を必要としない用途向け。
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # extern crate libfrugalos;
+//! use libfrugalos::client::blocking::config::Client;
+//! use libfrugalos::deadline::Deadline;
+//! use std::time::Duration;
+//!
+//! # fn main() -> libfrugalos::Result<()> {
+//! let client = Client::new("127.0.0.1:14278".parse().unwrap())?;
+//! let buckets = client.list_buckets(Deadline::new(Duration::from_secs(5)))?;
+//! # Ok(())
+//! # }
+//! ```
+use fibers::executor::ThreadPoolExecutorHandle;
+use fibers::{Executor, Spawn, ThreadPoolExecutor};
+use fibers_rpc::client::{ClientService, ClientServiceHandle as RpcServiceHandle};
+use futures::sync::mpsc;
+use futures::{stream, Future, Stream};
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use trackable::error::ErrorKindExt;
+
+use super::{spawn_future, spawn_stream, terminated};
+use {Error, ErrorKind, Result};
+
+pub mod config;
+pub mod frugalos;
+pub mod mds;
+
+/// 同期的なクライアントが要求の処理に使用するエクゼキュータとRPCサービス。
+///
+/// `clone`されたインスタンス間で、エクゼキュータとRPCサービスは共有される。
+/// 全てのインスタンスが破棄されると、エクゼキュータのスレッドは停止する。
+///
+/// エクゼキュータやRPCサービスが異常終了した場合には、以降の呼び出しはそのエラーを返す。
+#[derive(Debug, Clone)]
+pub struct Runtime {
+    inner: Arc<RuntimeInner>,
+}
+impl Runtime {
+    /// 新しい`Runtime`インスタンスを生成する。
+    ///
+    /// エクゼキュータを駆動するためのスレッドが起動される。
+    pub fn new() -> Result<Self> {
+        let mut executor = track!(ThreadPoolExecutor::new().map_err(Error::from))?;
+        let failure = Arc::new(Mutex::new(None));
+        let rpc_service = ClientService::new(executor.handle());
+        let rpc_service_handle = rpc_service.handle();
+        let rpc_service_failure = Arc::clone(&failure);
+        executor.spawn(rpc_service.map_err(move |e| {
+            set_failure(
+                &rpc_service_failure,
+                track!(Error::from(ErrorKind::Other.cause(e))),
+            );
+        }));
+
+        let executor_handle = executor.handle();
+        let executor_failure = Arc::clone(&failure);
+        let (stop_tx, stop_rx) = std_mpsc::channel();
+        thread::spawn(move || {
+            while let Err(std_mpsc::TryRecvError::Empty) = stop_rx.try_recv() {
+                if let Err(e) = executor.run_once() {
+                    set_failure(&executor_failure, track!(Error::from(e)));
+                    break;
+                }
+            }
+        });
+        Ok(Runtime {
+            inner: Arc::new(RuntimeInner {
+                executor: executor_handle,
+                rpc_service: rpc_service_handle,
+                failure,
+                _stop_tx: stop_tx,
+            }),
+        })
+    }
+
+    /// RPCサービスのハンドルを返す。
+    ///
+    /// 非同期版のクライアントを、このランタイム上で動作させるために使用できる。
+    pub fn rpc_service(&self) -> &RpcServiceHandle {
+        &self.inner.rpc_service
+    }
+
+    /// `future`をエクゼキュータ上で実行し、その完了を待つ。
+    pub fn block_on<F>(&self, future: F) -> Result<F::Item>
+    where
+        F: Future<Error = Error> + Send + 'static,
+        F::Item: Send + 'static,
+    {
+        track!(self.check())?;
+        match spawn_future(&self.inner.executor, future).wait() {
+            Ok(result) => track!(result),
+            Err(_) => Err(track!(self.failure())),
+        }
+    }
+
+    /// `stream`をエクゼキュータ上で実行し、その要素を順に返す`Iterator`に変換する。
+    pub fn iter<S>(&self, stream: S) -> Iter<S::Item>
+    where
+        S: Stream<Error = Error> + Send + 'static,
+        S::Item: Send + 'static,
+    {
+        Iter {
+            inner: spawn_stream(&self.inner.executor, stream).wait(),
+            runtime: self.clone(),
+            is_finished: false,
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        match *self.inner.failure.lock().expect("Never fails") {
+            None => Ok(()),
+            Some(ref e) => Err(e.clone()),
+        }
+    }
+
+    fn failure(&self) -> Error {
+        self.check().err().unwrap_or_else(terminated)
+    }
+}
+
+#[derive(Debug)]
+struct RuntimeInner {
+    executor: ThreadPoolExecutorHandle,
+    rpc_service: RpcServiceHandle,
+    failure: Arc<Mutex<Option<Error>>>,
+
+    // NOTE: 破棄されるとエクゼキュータのスレッドが停止する
+    _stop_tx: std_mpsc::Sender<()>,
+}
+
+fn set_failure(failure: &Mutex<Option<Error>>, e: Error) {
+    let mut failure = failure.lock().expect("Never fails");
+    if failure.is_none() {
+        *failure = Some(e);
+    }
+}
+
+/// ストリームを返すRPCの同期版が返す`Iterator`。
+///
+/// エラーが発生した場合には、そのエラーを最後の要素として終端する。
+/// ストリームの終端に達する前にエクゼキュータ上の処理が停止した場合にも、
+/// 一覧が途中で途切れたことを示すエラーを最後の要素として返す。
+///
+/// 破棄された場合には、残りの要求の発行も中断される。
+#[derive(Debug)]
+pub struct Iter<T> {
+    inner: stream::Wait<mpsc::Receiver<Result<Option<T>>>>,
+
+    // NOTE: 反復中にエクゼキュータのスレッドが停止しないように保持する
+    runtime: Runtime,
+    is_finished: bool,
+}
+impl<T> Iterator for Iter<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        match self.inner.next() {
+            Some(Ok(Ok(Some(item)))) => Some(Ok(item)),
+            Some(Ok(Ok(None))) => {
+                self.is_finished = true;
+                None
+            }
+            Some(Ok(Err(e))) => {
+                self.is_finished = true;
+                Some(Err(e))
+            }
+            None | Some(Err(())) => {
+                self.is_finished = true;
+                Some(Err(track!(self.runtime.failure())))
+            }
+        }
+    }
+}
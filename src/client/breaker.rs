@@ -0,0 +1,327 @@
+//! 宛先サーバ毎のサーキットブレーカ。
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use {ErrorKind, Result};
+
+/// サーキットブレーカの設定。
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// 回路を開くまでに許容される、連続した通信失敗の回数。
+    pub failure_threshold: usize,
+
+    /// 回路を開いてから、疎通確認のための要求を許可するまでの時間。
+    pub open_duration: Duration,
+
+    /// 回路を閉じるまでに必要な、疎通確認の連続成功回数。
+    pub success_threshold: usize,
+}
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(10),
+            success_threshold: 1,
+        }
+    }
+}
+
+/// 回路の状態。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// 閉状態。要求は通常通り送信される。
+    Closed,
+
+    /// 開状態。要求は送信されずに`ErrorKind::Unavailable`で失敗する。
+    Open,
+
+    /// 半開状態。疎通確認のための要求が一つずつ送信される。
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        consecutive_failures: usize,
+    },
+    Open {
+        since: Instant,
+    },
+    HalfOpen {
+        probe_started_at: Option<Instant>,
+        consecutive_successes: usize,
+    },
+}
+
+/// 宛先サーバ毎のサーキットブレーカ。
+///
+/// 通信に失敗し続けているサーバへの要求を即座に失敗させることで、
+/// デッドラインまで応答を待ち続けることを防ぐ。
+/// 一定時間が経過すると疎通確認のための要求が許可され、成功すれば元の状態に戻る。
+///
+/// `clone`されたインスタンス間で状態は共有されるため、複数のクライアントで同じインスタンスを利用できる。
+/// 通信の失敗とみなされるのは`ErrorKind::Unavailable`と`ErrorKind::Timeout`のみである。
+/// ただし、呼び出し側が指定したデッドラインに達したことによるタイムアウトは、失敗とはみなされない。
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    states: Arc<Mutex<HashMap<SocketAddr, State>>>,
+}
+impl CircuitBreaker {
+    /// 新しい`CircuitBreaker`インスタンスを生成する。
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            config,
+            states: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 設定を返す。
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// `server`に対応する回路の状態を返す。
+    pub fn state(&self, server: SocketAddr) -> CircuitState {
+        let states = self.states.lock().expect("Never fails");
+        match states.get(&server) {
+            None | Some(State::Closed { .. }) => CircuitState::Closed,
+            Some(State::Open { .. }) => CircuitState::Open,
+            Some(State::HalfOpen { .. }) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// `server`に対応する回路を閉状態に戻す。
+    pub fn reset(&self, server: SocketAddr) {
+        self.states.lock().expect("Never fails").remove(&server);
+    }
+
+    /// `server`への要求の送信可否を判定する。
+    ///
+    /// 送信できない場合には`ErrorKind::Unavailable`が返される。
+    pub(super) fn try_acquire(&self, server: SocketAddr) -> Result<()> {
+        let now = Instant::now();
+        let mut states = self.states.lock().expect("Never fails");
+        let state = match states.get_mut(&server) {
+            None => return Ok(()),
+            Some(state) => state,
+        };
+        match *state {
+            State::Closed { .. } => {}
+            State::Open { since } => {
+                track_assert!(
+                    now.duration_since(since) >= self.config.open_duration,
+                    ErrorKind::Unavailable,
+                    "Circuit open: server={}",
+                    server
+                );
+                *state = State::HalfOpen {
+                    probe_started_at: Some(now),
+                    consecutive_successes: 0,
+                };
+            }
+            State::HalfOpen {
+                ref mut probe_started_at,
+                ..
+            } => {
+                // NOTE: 疎通確認の要求が応答前に破棄された場合に備えて、一定時間経過後は次の要求を許可する
+                if let Some(started_at) = *probe_started_at {
+                    track_assert!(
+                        now.duration_since(started_at) >= self.config.open_duration,
+                        ErrorKind::Unavailable,
+                        "Circuit half-open: server={}",
+                        server
+                    );
+                }
+                *probe_started_at = Some(now);
+            }
+        }
+        Ok(())
+    }
+
+    /// `server`との通信の成功を記録する。
+    pub(super) fn record_success(&self, server: SocketAddr) {
+        let mut states = self.states.lock().expect("Never fails");
+        let is_closed = match states.get_mut(&server) {
+            None | Some(State::Closed { .. }) => true,
+            Some(State::Open { .. }) => false,
+            Some(State::HalfOpen {
+                ref mut probe_started_at,
+                ref mut consecutive_successes,
+            }) => {
+                *probe_started_at = None;
+                *consecutive_successes += 1;
+                *consecutive_successes >= self.config.success_threshold
+            }
+        };
+        if is_closed {
+            states.remove(&server);
+        }
+    }
+
+    /// `server`との通信の結果を、成功とも失敗ともみなさずに破棄する。
+    ///
+    /// 半開状態の場合には、次の疎通確認のための要求が許可される。
+    pub(super) fn release(&self, server: SocketAddr) {
+        let mut states = self.states.lock().expect("Never fails");
+        if let Some(State::HalfOpen {
+            ref mut probe_started_at,
+            ..
+        }) = states.get_mut(&server)
+        {
+            *probe_started_at = None;
+        }
+    }
+
+    /// `server`との通信の失敗を記録する。
+    pub(super) fn record_failure(&self, server: SocketAddr) {
+        let now = Instant::now();
+        let mut states = self.states.lock().expect("Never fails");
+        let state = states.entry(server).or_insert(State::Closed {
+            consecutive_failures: 0,
+        });
+        let next = match *state {
+            State::Closed {
+                consecutive_failures,
+            } => {
+                if consecutive_failures + 1 >= self.config.failure_threshold {
+                    State::Open { since: now }
+                } else {
+                    State::Closed {
+                        consecutive_failures: consecutive_failures + 1,
+                    }
+                }
+            }
+            State::Open { since } => State::Open { since },
+            State::HalfOpen { .. } => State::Open { since: now },
+        };
+        *state = next;
+    }
+}
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> SocketAddr {
+        "127.0.0.1:1000".parse().unwrap()
+    }
+
+    fn breaker(open_duration: Duration, success_threshold: usize) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 3,
+            open_duration,
+            success_threshold,
+        })
+    }
+
+    fn is_unavailable(result: Result<()>) -> bool {
+        result.err().map(|e| *e.kind()) == Some(ErrorKind::Unavailable)
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let b = breaker(Duration::from_secs(3600), 1);
+        b.record_failure(server());
+        b.record_failure(server());
+        assert_eq!(b.state(server()), CircuitState::Closed);
+
+        // 成功すると連続失敗回数はリセットされる
+        b.record_success(server());
+        b.record_failure(server());
+        b.record_failure(server());
+        assert_eq!(b.state(server()), CircuitState::Closed);
+        assert!(b.try_acquire(server()).is_ok());
+
+        b.record_failure(server());
+        assert_eq!(b.state(server()), CircuitState::Open);
+        assert!(is_unavailable(b.try_acquire(server())));
+
+        // 他のサーバには影響しない
+        let other = "127.0.0.1:2000".parse().unwrap();
+        assert_eq!(b.state(other), CircuitState::Closed);
+        assert!(b.try_acquire(other).is_ok());
+
+        b.reset(server());
+        assert_eq!(b.state(server()), CircuitState::Closed);
+    }
+
+    #[test]
+    fn half_open_works() {
+        let b = breaker(Duration::from_secs(0), 2);
+        for _ in 0..3 {
+            b.record_failure(server());
+        }
+        assert_eq!(b.state(server()), CircuitState::Open);
+
+        // 疎通確認に失敗すると、再び開状態になる
+        assert!(b.try_acquire(server()).is_ok());
+        assert_eq!(b.state(server()), CircuitState::HalfOpen);
+        b.record_failure(server());
+        assert_eq!(b.state(server()), CircuitState::Open);
+
+        // `success_threshold`回連続で成功すると、閉状態に戻る
+        assert!(b.try_acquire(server()).is_ok());
+        b.record_success(server());
+        assert_eq!(b.state(server()), CircuitState::HalfOpen);
+        assert!(b.try_acquire(server()).is_ok());
+        b.record_success(server());
+        assert_eq!(b.state(server()), CircuitState::Closed);
+    }
+
+    #[test]
+    fn half_open_allows_one_probe_at_a_time() {
+        let b = breaker(Duration::from_secs(3600), 1);
+        let since = Instant::now()
+            .checked_sub(Duration::from_secs(3600))
+            .unwrap();
+        b.states
+            .lock()
+            .unwrap()
+            .insert(server(), State::Open { since });
+
+        assert!(b.try_acquire(server()).is_ok());
+        assert_eq!(b.state(server()), CircuitState::HalfOpen);
+        assert!(is_unavailable(b.try_acquire(server())));
+
+        b.record_success(server());
+        assert_eq!(b.state(server()), CircuitState::Closed);
+        assert!(b.try_acquire(server()).is_ok());
+    }
+
+    #[test]
+    fn release_works() {
+        let b = breaker(Duration::from_secs(3600), 1);
+        for _ in 0..2 {
+            b.record_failure(server());
+        }
+
+        // 結果が破棄された要求は、失敗として数えられない
+        b.release(server());
+        b.release(server());
+        assert_eq!(b.state(server()), CircuitState::Closed);
+        b.record_failure(server());
+        assert_eq!(b.state(server()), CircuitState::Open);
+
+        // 半開状態の場合には、次の疎通確認が許可される
+        let since = Instant::now()
+            .checked_sub(Duration::from_secs(3600))
+            .unwrap();
+        b.states
+            .lock()
+            .unwrap()
+            .insert(server(), State::Open { since });
+        assert!(b.try_acquire(server()).is_ok());
+        assert!(is_unavailable(b.try_acquire(server())));
+        b.release(server());
+        assert_eq!(b.state(server()), CircuitState::HalfOpen);
+        assert!(b.try_acquire(server()).is_ok());
+    }
+}
@@ -0,0 +1,304 @@
+//! クラスタ全体を扱うためのクライアント。
+use fibers_rpc::client::ClientServiceHandle as RpcServiceHandle;
+use futures::{self, Future, Stream};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::config::Client as ConfigClient;
+use super::frugalos::Client as FrugalosClient;
+use super::mds::Client as MdsClient;
+use super::retry::RetryPolicy;
+use consistency::ReadConsistency;
+use deadline::Deadline;
+use entity::bucket::BucketId;
+use entity::object::{ObjectId, ObjectInfo, ObjectSummary, ObjectVersion};
+use expect::Expect;
+use multiplicity::MultiplicityConfig;
+use {Error, ErrorKind, Result};
+
+/// `Cluster::list_objects`で同時に問い合わせるセグメントの数。
+const LIST_CONCURRENCY: usize = 8;
+
+/// `Cluster`のビルダ。
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    contact_servers: Vec<SocketAddr>,
+    timeout: Duration,
+    retry_policy: Option<RetryPolicy>,
+    consistency: ReadConsistency,
+    multiplicity_config: MultiplicityConfig,
+}
+impl ClientBuilder {
+    /// 新しい`ClientBuilder`インスタンスを生成する。
+    ///
+    /// `contact_servers`は、構成管理系APIの問い合わせ先および、オブジェクト操作の要求の送信先として使用される。
+    pub fn new(contact_servers: Vec<SocketAddr>) -> Self {
+        ClientBuilder {
+            contact_servers,
+            timeout: Duration::from_secs(5),
+            retry_policy: None,
+            consistency: ReadConsistency::default(),
+            multiplicity_config: MultiplicityConfig::default(),
+        }
+    }
+
+    /// 各操作のタイムアウトを設定する。
+    ///
+    /// `Cluster`の各メソッドは、呼び出し時点からこの時間が経過した時刻をデッドラインとする。
+    ///
+    /// デフォルト値は`5`秒。
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 全てのクライアントに適用する再試行ポリシーを設定する。
+    ///
+    /// 指定されなかった場合には、各クライアントのデフォルトのポリシーが使用される。
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// 読み込み系の操作のデフォルトの整合性レベルを設定する。
+    pub fn consistency(&mut self, consistency: ReadConsistency) -> &mut Self {
+        self.consistency = consistency;
+        self
+    }
+
+    /// 書き込み系の操作のデフォルトの多重度設定を設定する。
+    pub fn multiplicity_config(&mut self, multiplicity_config: MultiplicityConfig) -> &mut Self {
+        self.multiplicity_config = multiplicity_config;
+        self
+    }
+
+    /// 指定の設定で`Cluster`を生成する。
+    ///
+    /// 問い合わせ先サーバが空の場合には`ErrorKind::InvalidInput`が返される。
+    pub fn finish(&self, rpc_service: RpcServiceHandle) -> Result<Cluster> {
+        let mut config = track!(ConfigClient::with_contact_servers(
+            self.contact_servers.clone(),
+            rpc_service.clone()
+        ))?;
+        let mut frugalos = track!(FrugalosClient::with_servers(
+            self.contact_servers.clone(),
+            rpc_service.clone()
+        ))?;
+        if let Some(ref retry_policy) = self.retry_policy {
+            config = config.with_retry_policy(retry_policy.clone());
+            frugalos = frugalos.with_retry_policy(retry_policy.clone());
+        }
+        Ok(Cluster {
+            config,
+            frugalos,
+            rpc_service,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.clone(),
+            consistency: self.consistency.clone(),
+            multiplicity_config: self.multiplicity_config.clone(),
+            mds_clients: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+}
+
+/// クラスタのハンドル。
+///
+/// 構成管理系API、オブジェクト操作およびMDSの各クライアントをまとめて提供する。
+/// オブジェクト操作用の各メソッドは、`ClientBuilder`で指定されたタイムアウトや整合性レベル等を使用する。
+/// 個別に指定したい場合には、`frugalos`が返すクライアントを直接使用すること。
+///
+/// セグメント毎のMDSクライアントはキャッシュされ、`clone`されたインスタンス間で共有される。
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    config: ConfigClient,
+    frugalos: FrugalosClient,
+    rpc_service: RpcServiceHandle,
+    timeout: Duration,
+    retry_policy: Option<RetryPolicy>,
+    consistency: ReadConsistency,
+    multiplicity_config: MultiplicityConfig,
+    mds_clients: Arc<Mutex<HashMap<(BucketId, u16), MdsClient>>>,
+}
+impl Cluster {
+    /// 構成管理系API用のクライアントを返す。
+    pub fn config(&self) -> &ConfigClient {
+        &self.config
+    }
+
+    /// オブジェクト操作用のクライアントを返す。
+    pub fn frugalos(&self) -> &FrugalosClient {
+        &self.frugalos
+    }
+
+    /// 現在時刻に、設定されたタイムアウトを加えたデッドラインを返す。
+    pub fn deadline(&self) -> Deadline {
+        Deadline::new(self.timeout)
+    }
+
+    /// 読み込み系の操作のデフォルトの整合性レベルを返す。
+    pub fn consistency(&self) -> &ReadConsistency {
+        &self.consistency
+    }
+
+    /// 書き込み系の操作のデフォルトの多重度設定を返す。
+    pub fn multiplicity_config(&self) -> &MultiplicityConfig {
+        &self.multiplicity_config
+    }
+
+    /// オブジェクトを取得する。
+    pub fn get_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+    ) -> impl Future<Item = Option<(ObjectInfo, Vec<u8>)>, Error = Error> {
+        self.frugalos.get_object(
+            bucket_id,
+            object_id,
+            self.deadline(),
+            Expect::Any,
+            self.consistency.clone(),
+        )
+    }
+
+    /// オブジェクトの情報を取得する。
+    pub fn head_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+    ) -> impl Future<Item = Option<ObjectInfo>, Error = Error> {
+        self.frugalos.head_object(
+            bucket_id,
+            object_id,
+            self.deadline(),
+            Expect::Any,
+            self.consistency.clone(),
+            false,
+        )
+    }
+
+    /// オブジェクトを保存する。
+    ///
+    /// 返り値の二番目の要素は、新規に作成されたオブジェクトかどうかを示す。
+    pub fn put_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        content: Vec<u8>,
+    ) -> impl Future<Item = (ObjectVersion, bool), Error = Error> {
+        self.frugalos.put_object(
+            bucket_id,
+            object_id,
+            content,
+            self.deadline(),
+            Expect::Any,
+            self.multiplicity_config.clone(),
+        )
+    }
+
+    /// オブジェクトを削除する。
+    pub fn delete_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+    ) -> impl Future<Item = Option<ObjectVersion>, Error = Error> {
+        self.frugalos
+            .delete_object(bucket_id, object_id, self.deadline(), Expect::Any)
+    }
+
+    /// バケツ内の全てのオブジェクトの一覧を返す。
+    ///
+    /// セグメント間でのオブジェクトの順序は保証されない。
+    pub fn list_objects(
+        &self,
+        bucket_id: BucketId,
+    ) -> impl Stream<Item = ObjectSummary, Error = Error> {
+        self.frugalos.list_all_objects(
+            &self.config,
+            bucket_id,
+            self.consistency.clone(),
+            LIST_CONCURRENCY,
+            self.deadline(),
+        )
+    }
+
+    /// `segment`を担当するMDSのクライアントを返す。
+    ///
+    /// 担当ノードはクラスタ構成から取得され、結果はキャッシュされる。
+    /// 最初のノードが要求の送信先となり、リーダが判明した以降はリーダに送信される。
+    ///
+    /// バケツやセグメントが存在しない場合には`ErrorKind::InvalidInput`が返される。
+    pub fn mds(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+    ) -> impl Future<Item = MdsClient, Error = Error> {
+        let key = (bucket_id, segment);
+        if let Some(client) = self.mds_clients.lock().expect("Never fails").get(&key) {
+            return futures::future::Either::A(futures::future::ok(client.clone()));
+        }
+
+        let rpc_service = self.rpc_service.clone();
+        let retry_policy = self.retry_policy.clone();
+        let mds_clients = Arc::clone(&self.mds_clients);
+        let future = self
+            .config
+            .get_segment_nodes(key.0.clone(), segment, self.deadline())
+            .and_then(move |nodes| {
+                let node = track_assert_some!(
+                    nodes.and_then(|nodes| nodes.into_iter().next()),
+                    ErrorKind::InvalidInput,
+                    "No such segment: bucket={:?}, segment={}",
+                    key.0,
+                    segment
+                );
+                let mut client = MdsClient::new(node, rpc_service);
+                if let Some(retry_policy) = retry_policy {
+                    client = client.with_retry_policy(retry_policy);
+                }
+                let client = mds_clients
+                    .lock()
+                    .expect("Never fails")
+                    .entry(key)
+                    .or_insert(client)
+                    .clone();
+                Ok(client)
+            });
+        futures::future::Either::B(future)
+    }
+
+    /// `object_id`が属するセグメントを担当するMDSのクライアントを返す。
+    ///
+    /// バケツが存在しない場合には`ErrorKind::InvalidInput`が返される。
+    pub fn mds_for_object(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+    ) -> impl Future<Item = MdsClient, Error = Error> {
+        let this = self.clone();
+        self.config
+            .get_bucket(bucket_id.clone(), self.deadline())
+            .and_then(move |bucket| {
+                let bucket = track_assert_some!(
+                    bucket,
+                    ErrorKind::InvalidInput,
+                    "No such bucket: {:?}",
+                    bucket_id
+                );
+                let segment = track!(bucket.segment_of(&object_id))?;
+                Ok(this.mds(bucket_id, segment))
+            })
+            .flatten()
+    }
+
+    /// キャッシュされている`segment`のMDSクライアントを破棄する。
+    ///
+    /// セグメントの担当ノードが変更された場合に使用する。
+    pub fn invalidate_mds(&self, bucket_id: BucketId, segment: u16) {
+        self.mds_clients
+            .lock()
+            .expect("Never fails")
+            .remove(&(bucket_id, segment));
+    }
+}
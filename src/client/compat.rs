@@ -0,0 +1,139 @@
+//! `std::future::Future`および`futures`0.3系の`Stream`との互換レイヤ。
+//!
+//! `compat`フィーチャが有効な場合にのみ利用可能。
+//!
+//! 各クライアントが返すFutureやStreamは`fibers`のタイマーに依存しているため、
+//! `fibers`以外のエクゼキュータ上で直接ポーリングすると、再試行やヘッジ要求が起動されないことがある。
+//! そのため、このモジュールの変換処理では、元のFutureやStreamを`fibers`のエクゼキュータ上で実行し、
+//! その結果をチャンネル経由で受け取るようにしている。
+//!
+//! # Examples
+//!
+//! ```no_run,edition2018
+//! use fibers::{Executor, ThreadPoolExecutor};
+//! use fibers_rpc::client::ClientService;
+//! use libfrugalos::client::compat::CompatFutureExt;
+//! use libfrugalos::client::frugalos::Client;
+//! use libfrugalos::consistency::ReadConsistency;
+//! use libfrugalos::deadline::Deadline;
+//! use libfrugalos::expect::Expect;
+//! use std::time::Duration;
+//!
+//! # async fn run() -> libfrugalos::Result<()> {
+//! let executor = ThreadPoolExecutor::new().unwrap();
+//! let rpc_service = ClientService::new(executor.handle());
+//! let client = Client::new("127.0.0.1:14278".parse().unwrap(), rpc_service.handle());
+//!
+//! let object = client
+//!     .get_object(
+//!         "bucket".to_owned(),
+//!         "object".to_owned(),
+//!         Deadline::new(Duration::from_secs(5)),
+//!         Expect::Any,
+//!         ReadConsistency::Consistent,
+//!     )
+//!     .compat(&executor.handle())
+//!     .await?;
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use fibers::Spawn;
+use futures::sync::{mpsc, oneshot};
+use futures::{Future, Stream};
+use futures03::compat::Compat01As03;
+use futures03::Stream as Stream03;
+use std::future::Future as StdFuture;
+use std::pin::Pin;
+use std::task::{self, Context};
+
+use super::{spawn_future, spawn_stream, terminated};
+use {Error, Result};
+
+/// クライアントが返すFutureを`std::future::Future`に変換するための拡張トレイト。
+pub trait CompatFutureExt: Future<Error = Error> + Sized {
+    /// `spawner`上で実行され、その結果を返す`std::future::Future`に変換する。
+    ///
+    /// 返されたFutureが破棄された場合には、元のFutureの実行も中断される。
+    fn compat<S: Spawn>(self, spawner: &S) -> CompatFuture<Self::Item>;
+}
+impl<F> CompatFutureExt for F
+where
+    F: Future<Error = Error> + Send + 'static,
+    F::Item: Send + 'static,
+{
+    fn compat<S: Spawn>(self, spawner: &S) -> CompatFuture<Self::Item> {
+        CompatFuture(Compat01As03::new(spawn_future(spawner, self)))
+    }
+}
+
+/// クライアントが返すStreamを`futures`0.3系の`Stream`に変換するための拡張トレイト。
+pub trait CompatStreamExt: Stream<Error = Error> + Sized {
+    /// `spawner`上で実行され、その要素を返す`futures`0.3系の`Stream`に変換する。
+    ///
+    /// 元のStreamがエラーを返した場合には、そのエラーを最後の要素として終端する。
+    /// 返されたStreamが破棄された場合には、元のStreamの実行も中断される。
+    fn compat<S: Spawn>(self, spawner: &S) -> CompatStream<Self::Item>;
+}
+impl<T> CompatStreamExt for T
+where
+    T: Stream<Error = Error> + Send + 'static,
+    T::Item: Send + 'static,
+{
+    fn compat<S: Spawn>(self, spawner: &S) -> CompatStream<Self::Item> {
+        CompatStream {
+            inner: Compat01As03::new(spawn_stream(spawner, self)),
+            is_finished: false,
+        }
+    }
+}
+
+/// `CompatFutureExt::compat`が返す`std::future::Future`。
+#[derive(Debug)]
+pub struct CompatFuture<T>(Compat01As03<oneshot::Receiver<Result<T>>>);
+impl<T> StdFuture for CompatFuture<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> task::Poll<Self::Output> {
+        match Pin::new(&mut self.0).poll(cx) {
+            task::Poll::Pending => task::Poll::Pending,
+            task::Poll::Ready(Ok(result)) => task::Poll::Ready(result),
+            task::Poll::Ready(Err(_)) => task::Poll::Ready(Err(track!(terminated()))),
+        }
+    }
+}
+
+/// `CompatStreamExt::compat`が返す`futures`0.3系の`Stream`。
+///
+/// 元のStreamが終端する前に実行中のファイバーが停止した場合には、
+/// 一覧が途中で途切れたことを示すために`ErrorKind::Other`を最後の要素として返す。
+#[derive(Debug)]
+pub struct CompatStream<T> {
+    inner: Compat01As03<mpsc::Receiver<Result<Option<T>>>>,
+    is_finished: bool,
+}
+impl<T> Stream03 for CompatStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> task::Poll<Option<Self::Item>> {
+        if self.is_finished {
+            return task::Poll::Ready(None);
+        }
+        match Pin::new(&mut self.inner).poll_next(cx) {
+            task::Poll::Pending => task::Poll::Pending,
+            task::Poll::Ready(Some(Ok(Ok(Some(item))))) => task::Poll::Ready(Some(Ok(item))),
+            task::Poll::Ready(Some(Ok(Ok(None)))) => {
+                self.is_finished = true;
+                task::Poll::Ready(None)
+            }
+            task::Poll::Ready(Some(Ok(Err(e)))) => {
+                self.is_finished = true;
+                task::Poll::Ready(Some(Err(e)))
+            }
+            task::Poll::Ready(Some(Err(()))) | task::Poll::Ready(None) => {
+                self.is_finished = true;
+                task::Poll::Ready(Some(Err(track!(terminated()))))
+            }
+        }
+    }
+}
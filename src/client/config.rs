@@ -3,133 +3,310 @@ use fibers_rpc::client::ClientServiceHandle as RpcServiceHandle;
 use fibers_rpc::Call as RpcCall;
 use futures::{Async, Future, Poll};
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use super::Response;
+use super::retry::RetryPolicy;
+use super::{Guarded, Response, Retry};
+use deadline::Deadline;
 use entity::bucket::{Bucket, BucketId, BucketSummary};
 use entity::device::{Device, DeviceId, DeviceSummary};
+use entity::node::RemoteNodeId;
 use entity::server::{Server, ServerId, ServerSummary};
 use schema::config;
 use {Error, ErrorKind, Result};
 
+/// `ErrorKind::NotLeader`を受けて、リーダを問い合わせ直して要求を再送する最大回数。
+const MAX_LEADER_REDIRECTS: usize = 1;
+
 /// RPCクライアント。
-#[derive(Debug)]
+///
+/// 判明したリーダのアドレスはキャッシュされ、以降の要求はリーダに直接送信される。
+/// キャッシュは`clone`されたインスタンス間で共有される。
+///
+/// 各メソッドに指定された`deadline`は、リーダの問い合わせや再試行も含めた処理全体に適用される。
+/// デッドラインを過ぎた場合には`ErrorKind::Timeout`が返される。
+#[derive(Debug, Clone)]
 pub struct Client {
-    contact_server: SocketAddr,
+    contact_servers: Vec<SocketAddr>,
+    leader: Arc<Mutex<Option<SocketAddr>>>,
     rpc_service: RpcServiceHandle,
+    retry_policy: RetryPolicy,
 }
 impl Client {
     /// 新しい`Client`インスタンスを生成する。
+    ///
+    /// `ErrorKind::NotLeader`が発生した場合には、再試行ポリシーに依らず、
+    /// リーダを問い合わせ直した上で一度だけ要求が再送される。
+    ///
+    /// デフォルトの再試行ポリシーでは、通信エラー(`ErrorKind::Unavailable`)が発生した場合に、
+    /// リーダを問い合わせ直した上で一度だけ再試行が行われる。
+    /// タイムアウトした要求はサーバ側で適用済みの可能性があるため、デフォルトでは再試行されない。
+    /// 必要な場合には`with_retry_policy`で`ErrorKind::Timeout`を再試行の対象に含めること。
     pub fn new(contact_server: SocketAddr, rpc_service: RpcServiceHandle) -> Self {
         Client {
-            contact_server,
+            contact_servers: vec![contact_server],
+            leader: Arc::new(Mutex::new(None)),
             rpc_service,
+            retry_policy: default_retry_policy(),
         }
     }
 
+    /// 複数の問い合わせ先サーバを指定して、新しい`Client`インスタンスを生成する。
+    ///
+    /// リーダの問い合わせは`contact_servers`の先頭から順に行われ、
+    /// 応答が得られなかった場合には次のサーバが試される。
+    ///
+    /// `contact_servers`が空の場合には`ErrorKind::InvalidInput`が返される。
+    pub fn with_contact_servers(
+        contact_servers: Vec<SocketAddr>,
+        rpc_service: RpcServiceHandle,
+    ) -> Result<Self> {
+        track_assert!(
+            !contact_servers.is_empty(),
+            ErrorKind::InvalidInput,
+            "No contact servers"
+        );
+        Ok(Client {
+            contact_servers,
+            leader: Arc::new(Mutex::new(None)),
+            rpc_service,
+            retry_policy: default_retry_policy(),
+        })
+    }
+
+    /// 再試行ポリシーを変更する。
+    ///
+    /// 通信エラーによる再試行の前には、リーダの問い合わせが行われる。
+    /// `ErrorKind::NotLeader`によるリーダの切り替えは、このポリシーとは独立に行われる。
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 再試行ポリシーを返す。
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// 問い合わせ先サーバの一覧を返す。
+    pub fn contact_servers(&self) -> &[SocketAddr] {
+        &self.contact_servers
+    }
+
+    /// キャッシュされているリーダのアドレスを返す。
+    ///
+    /// リーダが未判明の場合には`None`が返される。
+    pub fn leader(&self) -> Option<SocketAddr> {
+        *self.leader.lock().expect("Never fails")
+    }
+
+    /// リーダのキャッシュを破棄する。
+    ///
+    /// 次の要求の送信前に、問い合わせ先サーバに対してリーダの問い合わせが行われる。
+    pub fn invalidate_leader(&self) {
+        *self.leader.lock().expect("Never fails") = None;
+    }
+
     /// `ListServersRpc`を実行する。
-    pub fn list_servers(&self) -> impl Future<Item = Vec<ServerSummary>, Error = Error> {
-        Call::<config::ListServersRpc, _>::new(self, ())
+    pub fn list_servers(
+        &self,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<ServerSummary>, Error = Error> {
+        Call::<config::ListServersRpc, _>::new(self, deadline, ())
     }
 
     /// `GetServerRpc`を実行する。
     pub fn get_server(
         &self,
         server: ServerId,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<Server>, Error = Error> {
-        Call::<config::GetServerRpc, _>::new(self, server)
+        Call::<config::GetServerRpc, _>::new(self, deadline, server)
     }
 
     /// `PutServerRpc`を実行する。
-    pub fn put_server(&self, server: Server) -> impl Future<Item = Server, Error = Error> {
-        Call::<config::PutServerRpc, _>::new(self, server)
+    pub fn put_server(
+        &self,
+        server: Server,
+        deadline: Deadline,
+    ) -> impl Future<Item = Server, Error = Error> {
+        Call::<config::PutServerRpc, _>::new(self, deadline, server)
     }
 
     /// `DeleteServerRpc`を実行する。
     pub fn delete_server(
         &self,
         server: ServerId,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<Server>, Error = Error> {
-        Call::<config::DeleteServerRpc, _>::new(self, server)
+        Call::<config::DeleteServerRpc, _>::new(self, deadline, server)
     }
 
     /// `ListDevicesRpc`を実行する。
-    pub fn list_devices(&self) -> impl Future<Item = Vec<DeviceSummary>, Error = Error> {
-        Call::<config::ListDevicesRpc, _>::new(self, ())
+    pub fn list_devices(
+        &self,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<DeviceSummary>, Error = Error> {
+        Call::<config::ListDevicesRpc, _>::new(self, deadline, ())
     }
 
     /// `GetDeviceRpc`を実行する。
     pub fn get_device(
         &self,
         device: DeviceId,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<Device>, Error = Error> {
-        Call::<config::GetDeviceRpc, _>::new(self, device)
+        Call::<config::GetDeviceRpc, _>::new(self, deadline, device)
     }
 
     /// `PutDeviceRpc`を実行する。
-    pub fn put_device(&self, device: Device) -> impl Future<Item = Device, Error = Error> {
-        Call::<config::PutDeviceRpc, _>::new(self, device)
+    pub fn put_device(
+        &self,
+        device: Device,
+        deadline: Deadline,
+    ) -> impl Future<Item = Device, Error = Error> {
+        Call::<config::PutDeviceRpc, _>::new(self, deadline, device)
     }
 
     /// `DeleteDeviceRpc`を実行する。
     pub fn delete_device(
         &self,
         device: DeviceId,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<Device>, Error = Error> {
-        Call::<config::DeleteDeviceRpc, _>::new(self, device)
+        Call::<config::DeleteDeviceRpc, _>::new(self, deadline, device)
     }
 
     /// `ListBucketsRpc`を実行する。
-    pub fn list_buckets(&self) -> impl Future<Item = Vec<BucketSummary>, Error = Error> {
-        Call::<config::ListBucketsRpc, _>::new(self, ())
+    pub fn list_buckets(
+        &self,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<BucketSummary>, Error = Error> {
+        Call::<config::ListBucketsRpc, _>::new(self, deadline, ())
     }
 
     /// `GetBucketRpc`を実行する。
     pub fn get_bucket(
         &self,
         bucket: BucketId,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<Bucket>, Error = Error> {
-        Call::<config::GetBucketRpc, _>::new(self, bucket)
+        Call::<config::GetBucketRpc, _>::new(self, deadline, bucket)
     }
 
     /// `PutBucketRpc`を実行する。
-    pub fn put_bucket(&self, bucket: Bucket) -> impl Future<Item = Bucket, Error = Error> {
-        Call::<config::PutBucketRpc, _>::new(self, bucket)
+    pub fn put_bucket(
+        &self,
+        bucket: Bucket,
+        deadline: Deadline,
+    ) -> impl Future<Item = Bucket, Error = Error> {
+        Call::<config::PutBucketRpc, _>::new(self, deadline, bucket)
     }
 
     /// `DeleteBucketRpc`を実行する。
     pub fn delete_bucket(
         &self,
         bucket: BucketId,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<Bucket>, Error = Error> {
-        Call::<config::DeleteBucketRpc, _>::new(self, bucket)
+        Call::<config::DeleteBucketRpc, _>::new(self, deadline, bucket)
+    }
+
+    /// `GetSegmentNodesRpc`を実行する。
+    ///
+    /// 返されるノード一覧の順序は、クラスタ構成上のメンバの順序に従う。
+    pub fn get_segment_nodes(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        deadline: Deadline,
+    ) -> impl Future<Item = Option<Vec<RemoteNodeId>>, Error = Error> {
+        let request = config::SegmentRequest { bucket_id, segment };
+        Call::<config::GetSegmentNodesRpc, _>::new(self, deadline, request)
+    }
+}
+
+fn default_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 2,
+        initial_backoff: Duration::from_secs(0),
+        jitter: false,
+        retryable_errors: vec![ErrorKind::Unavailable],
+        ..Default::default()
     }
 }
 
 #[derive(Debug)]
 struct Call<T: RpcCall, U> {
-    contact_server: SocketAddr,
+    contact_servers: Vec<SocketAddr>,
     rpc_service: RpcServiceHandle,
-    leader: Response<SocketAddr>,
+    leader_cache: Arc<Mutex<Option<SocketAddr>>>,
+    leader: Option<(usize, Guarded<Response<SocketAddr>>)>,
+    target: Option<SocketAddr>,
     request: T::Req,
-    response: Option<Response<U>>,
-    is_retried: bool,
+    response: Option<Guarded<Response<U>>>,
+    deadline: Deadline,
+    retry: Retry,
+    redirects: usize,
 }
 impl<T, U> Call<T, U>
 where
     U: Send + 'static,
     T: RpcCall<Res = Result<U>>,
     T::Req: Clone,
+    T::ReqEncoder: Default,
+    T::ResDecoder: Default,
 {
-    fn new(client: &Client, request: T::Req) -> Self {
-        let future =
-            config::GetLeaderRpc::client(&client.rpc_service).call(client.contact_server, ());
-        Call {
-            contact_server: client.contact_server,
+    fn new(client: &Client, deadline: Deadline, request: T::Req) -> Self {
+        let mut call = Call {
+            contact_servers: client.contact_servers.clone(),
             rpc_service: client.rpc_service.clone(),
-            leader: Response(future),
+            leader_cache: Arc::clone(&client.leader),
+            leader: None,
+            target: None,
             request,
             response: None,
-            is_retried: false,
+            deadline,
+            retry: Retry::new(client.retry_policy.clone(), deadline),
+            redirects: 0,
+        };
+        call.start();
+        call
+    }
+
+    fn start(&mut self) {
+        let leader = *self.leader_cache.lock().expect("Never fails");
+        if let Some(leader) = leader {
+            self.send(leader);
+        } else {
+            self.lookup_leader(0);
+        }
+    }
+
+    fn send(&mut self, leader: SocketAddr) {
+        let rpc_service = &self.rpc_service;
+        let request = &self.request;
+        let future = Guarded::new(None, leader, self.deadline, |timeout| {
+            Response::call::<T>(rpc_service, leader, request.clone(), timeout)
+        });
+        self.target = Some(leader);
+        self.response = Some(future);
+    }
+
+    fn lookup_leader(&mut self, index: usize) {
+        let contact_server = self.contact_servers[index];
+        let rpc_service = &self.rpc_service;
+        let future = Guarded::new(None, contact_server, self.deadline, |timeout| {
+            Response::call::<config::GetLeaderRpc>(rpc_service, contact_server, (), timeout)
+        });
+        self.leader = Some((index, future));
+    }
+
+    fn invalidate_leader(&mut self) {
+        let mut cache = self.leader_cache.lock().expect("Never fails");
+        if *cache == self.target {
+            *cache = None;
         }
     }
 }
@@ -146,35 +323,62 @@ where
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
         loop {
+            match track!(self.retry.poll())? {
+                Async::NotReady => break,
+                Async::Ready(true) => self.start(),
+                Async::Ready(false) => {}
+            }
+
             match self.response.poll() {
                 Err(e) => {
+                    track_assert!(!self.deadline.is_expired(), ErrorKind::Timeout; e, T::NAME);
+                    match *e.kind() {
+                        ErrorKind::NotLeader | ErrorKind::Unavailable | ErrorKind::Timeout => {
+                            self.invalidate_leader();
+                        }
+                        _ => {}
+                    }
+                    self.response = None;
                     if *e.kind() == ErrorKind::NotLeader {
+                        // NOTE: リーダの切り替えへの追従は、再試行ポリシーに依らず常に行う
                         track_assert!(
-                            !self.is_retried,
+                            self.redirects < MAX_LEADER_REDIRECTS,
                             ErrorKind::Unavailable,
                             "Unstable cluster: RPC={}",
                             T::NAME
                         );
-
-                        self.is_retried = true;
-                        let future = config::GetLeaderRpc::client(&self.rpc_service)
-                            .call(self.contact_server, ());
-                        self.leader = Response(future);
-                        self.response = None;
-                    } else {
+                        self.redirects += 1;
+                        self.lookup_leader(0);
+                        continue;
+                    }
+                    if let Err(e) = self.retry.schedule(e) {
                         return Err(track!(e, T::NAME));
                     }
+                    continue;
                 }
                 Ok(Async::NotReady) => break,
                 Ok(Async::Ready(None)) => {}
                 Ok(Async::Ready(Some(response))) => return Ok(Async::Ready(response)),
             }
 
-            if let Async::Ready(leader) = track!(self.leader.poll())? {
-                let future = T::client(&self.rpc_service).call(leader, self.request.clone());
-                self.response = Some(Response(future));
-            } else {
-                break;
+            let (index, polled) = match self.leader {
+                None => break,
+                Some((index, ref mut future)) => (index, future.poll()),
+            };
+            match polled {
+                Err(e) => {
+                    if index + 1 < self.contact_servers.len() {
+                        self.lookup_leader(index + 1);
+                    } else {
+                        return Err(track!(e, T::NAME));
+                    }
+                }
+                Ok(Async::Ready(leader)) => {
+                    self.leader = None;
+                    *self.leader_cache.lock().expect("Never fails") = Some(leader);
+                    self.send(leader);
+                }
+                Ok(Async::NotReady) => break,
             }
         }
         Ok(Async::NotReady)
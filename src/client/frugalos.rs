@@ -1,58 +1,295 @@
 //! Frugalosの公開API用のRPCクライアント。
+use fibers::sync::mpsc as fibers_mpsc;
 use fibers_rpc::client::ClientServiceHandle as RpcServiceHandle;
-use fibers_rpc::Call as RpcCall;
-use futures::Future;
-use std::collections::BTreeSet;
+use fibers_rpc::{Call as RpcCall, Cast as RpcCast};
+use futures::{self, Async, Future, Poll, Stream};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
 use std::net::SocketAddr;
 use std::ops::Range;
+use std::sync::mpsc as std_mpsc;
+use std::thread;
 use std::time::Duration;
+use trackable::error::ErrorKindExt;
 
-use super::Response;
+use super::breaker::CircuitBreaker;
+use super::config::Client as ConfigClient;
+use super::pool::{Balancing, Outstanding, ServerPool, ServerStatus};
+use super::retry::RetryPolicy;
+use super::{range_page, Guarded, Hedged, Pages, Response, Retry};
+use checksum::{Checksum, ChecksumAlgorithm, ChecksumHasher};
 use consistency::ReadConsistency;
+use deadline::Deadline;
 use entity::bucket::BucketId;
 use entity::device::DeviceId;
+use entity::job::JobId;
 use entity::object::{
-    DeleteObjectsByPrefixSummary, FragmentsSummary, ObjectId, ObjectPrefix, ObjectSummary,
-    ObjectVersion,
+    AttributeFilter, Attributes, DeleteObjectsByPrefixProgress, DeleteObjectsByPrefixSummary,
+    Expiry, FragmentsSummary, ObjectChunk, ObjectId, ObjectInfo, ObjectPrefix, ObjectSummary,
+    ObjectSummaryPage, ObjectVersion, UploadId,
 };
 use expect::Expect;
 use multiplicity::MultiplicityConfig;
 use repair::RepairConfig;
-use schema::frugalos;
-use Error;
+use schema::frugalos::{self, BucketEvents, ObjectItem, ObjectTarget, PutObjectItem};
+use time::UnixTimestamp;
+use {Error, ErrorKind, Result};
 
 /// RPCクライアント。
-#[derive(Debug)]
+///
+/// 複数のサーバが指定された場合には、要求は各サーバに振り分けられる。
+/// 通信に失敗したサーバは一時的に振り分け対象から外され、要求は別のサーバに再送される。
+/// ただし、書き込み系の要求は、サーバ側で適用済みの可能性があるため、タイムアウトした場合には再送されない。
+///
+/// 各メソッドに指定された`deadline`は、再送や再試行も含めた処理全体に適用される。
+/// デッドラインを過ぎた場合には`ErrorKind::Timeout`が返される。
+/// 複数の要求を発行するストリームでは、`deadline`は全ての要求を合わせた処理時間に対して適用される。
+/// ただし、オブジェクトの内容を断片単位で送受信する`get_object_stream`と`put_object_stream`は、
+/// 転送時間がオブジェクトのサイズに比例するため、デッドラインの代わりに断片毎のタイムアウトを受け取る。
+#[derive(Debug, Clone)]
 pub struct Client {
-    server: SocketAddr,
+    pool: ServerPool,
+    pinned: Option<SocketAddr>,
     rpc_service: RpcServiceHandle,
+    retry_policy: RetryPolicy,
+    hedging_delay: Option<Duration>,
+    breaker: Option<CircuitBreaker>,
 }
 impl Client {
     /// 新しい`Client`インスタンスを生成する。
+    ///
+    /// デフォルトでは再試行は行われない。
     pub fn new(server: SocketAddr, rpc_service: RpcServiceHandle) -> Self {
+        let pool = ServerPool::new(vec![server], Balancing::default()).expect("Never fails");
         Client {
-            server,
+            pool,
+            pinned: None,
             rpc_service,
+            retry_policy: RetryPolicy::no_retry(),
+            hedging_delay: None,
+            breaker: None,
         }
     }
 
-    /// `GetObjectRpc`を実行する。
+    /// 複数のサーバを指定して、新しい`Client`インスタンスを生成する。
+    ///
+    /// `servers`が空の場合には`ErrorKind::InvalidInput`が返される。
+    pub fn with_servers(servers: Vec<SocketAddr>, rpc_service: RpcServiceHandle) -> Result<Self> {
+        let pool = track!(ServerPool::new(servers, Balancing::default()))?;
+        Ok(Client {
+            pool,
+            pinned: None,
+            rpc_service,
+            retry_policy: RetryPolicy::no_retry(),
+            hedging_delay: None,
+            breaker: None,
+        })
+    }
+
+    /// クラスタに登録されている全てのサーバを`config`経由で取得し、新しい`Client`インスタンスを生成する。
+    pub fn discover(
+        config: &ConfigClient,
+        rpc_service: RpcServiceHandle,
+        deadline: Deadline,
+    ) -> impl Future<Item = Self, Error = Error> {
+        let config = config.clone();
+        config
+            .list_servers(deadline)
+            .and_then(move |summaries| {
+                let servers = summaries
+                    .into_iter()
+                    .map(move |summary| config.get_server(summary.id, deadline));
+                futures::future::join_all(servers)
+            })
+            .and_then(move |servers| {
+                let servers = servers
+                    .into_iter()
+                    .filter_map(|server| server.map(|s| s.addr()))
+                    .collect();
+                track!(Client::with_servers(servers, rpc_service))
+            })
+    }
+
+    /// 要求の振り分け方式を変更する。
+    pub fn with_balancing(mut self, balancing: Balancing) -> Self {
+        self.pool.set_balancing(balancing);
+        self
+    }
+
+    /// 要求の振り分け方式を返す。
+    pub fn balancing(&self) -> Balancing {
+        self.pool.balancing()
+    }
+
+    /// 各サーバの状態を返す。
+    pub fn server_statuses(&self) -> Vec<ServerStatus> {
+        self.pool.statuses()
+    }
+
+    /// ヘッジングを有効にする。
+    ///
+    /// `get_object`と`head_object`の実行時に、`delay`が経過しても応答が得られない場合には、
+    /// 別のサーバに同じ要求を送信し、先に成功した方の結果を返す。
+    /// 送信先となるサーバが一つしかない場合には、ヘッジングは行われない。
+    pub fn with_hedging(mut self, delay: Duration) -> Self {
+        self.hedging_delay = Some(delay);
+        self
+    }
+
+    /// ヘッジングの待ち時間を返す。
+    ///
+    /// ヘッジングが無効な場合には`None`が返される。
+    pub fn hedging_delay(&self) -> Option<Duration> {
+        self.hedging_delay
+    }
+
+    /// サーキットブレーカを設定する。
+    ///
+    /// 回路が開いているサーバへの要求は、送信されずに`ErrorKind::Unavailable`で失敗する。
+    /// 複数のサーバが指定されている場合には、要求は別のサーバに再送される。
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// 全ての要求を`server`に送信する`Client`を返す。
+    ///
+    /// サーバ上に状態を持つ一連の要求(e.g., 分割アップロード)に使用する。
+    fn pinned(&self, server: SocketAddr) -> Self {
+        let mut client = self.clone();
+        client.pinned = Some(server);
+        client
+    }
+
+    fn select_server(&self) -> SocketAddr {
+        self.pinned
+            .or_else(|| self.pool.select(&[]))
+            .expect("Never fails")
+    }
+
+    fn hedged_call<T, U>(&self, deadline: Deadline, request: T::Req) -> Hedged<Call<T, U>>
+    where
+        U: Send + 'static,
+        T: RpcCall<Res = Result<U>>,
+        T::Req: Clone + SetDeadline,
+        T::ReqEncoder: Default,
+        T::ResDecoder: Default,
+    {
+        let delay = match self.hedging_delay {
+            Some(delay) if self.pinned.is_none() && self.pool.len() > 1 => delay,
+            _ => return Hedged::new(Call::read(self, deadline, request)),
+        };
+        let primary_server = self.select_server();
+        let primary = Call::read(&self.pinned(primary_server), deadline, request.clone());
+        let client = self.clone();
+        Hedged::with_hedge(primary, delay, move || {
+            let server = client.pool.select(&[primary_server])?;
+            Some(Call::read(&client.pinned(server), deadline, request))
+        })
+    }
+
+    /// 再試行ポリシーを変更する。
+    ///
+    /// 要求にデッドラインが含まれる場合には、デッドラインを超えて再試行が行われることはない。
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 再試行ポリシーを返す。
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// `GetObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、応答の遅いサーバに加えて別のサーバにも要求が送信される。
+    ///
+    /// オブジェクトにチェックサムが付与されている場合には、取得した内容を検証する。
+    /// 一致しない場合には`ErrorKind::ChecksumMismatch`が返される。
     pub fn get_object(
         &self,
         bucket_id: BucketId,
         object_id: ObjectId,
-        deadline: Duration,
+        deadline: Deadline,
         expect: Expect,
         consistency: ReadConsistency,
-    ) -> impl Future<Item = Option<(ObjectVersion, Vec<u8>)>, Error = Error> {
+    ) -> impl Future<Item = Option<(ObjectInfo, Vec<u8>)>, Error = Error> {
         let request = frugalos::ObjectRequest {
             bucket_id,
             object_id,
-            deadline,
+            deadline: deadline.remaining(),
             expect,
             consistency: Some(consistency),
         };
-        Response(frugalos::GetObjectRpc::client(&self.rpc_service).call(self.server, request))
+        self.hedged_call::<frugalos::GetObjectInfoRpc, _>(deadline, request)
+            .and_then(|result| {
+                if let Some((ref info, ref content)) = result {
+                    track!(verify_content(info, content))?;
+                }
+                Ok(result)
+            })
+    }
+
+    /// `GetObjectRangeRpc`を実行する。
+    ///
+    /// 返される断片には、指定範囲のデータに加えて、オブジェクト全体のサイズとバージョンが含まれる。
+    pub fn get_object_range(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        range: Range<u64>,
+        deadline: Deadline,
+        expect: Expect,
+        consistency: ReadConsistency,
+    ) -> impl Future<Item = Option<ObjectChunk>, Error = Error> {
+        let request = frugalos::ObjectRangeRequest {
+            bucket_id,
+            object_id,
+            deadline: deadline.remaining(),
+            expect,
+            consistency,
+            range,
+        };
+        Call::<frugalos::GetObjectRangeRpc, _>::read(self, deadline, request)
+    }
+
+    /// `GetObjectChunkRpc`を繰り返し実行して、オブジェクトの内容を断片単位で取得する。
+    ///
+    /// 各断片は最大で`chunk_size`バイトとなるため、メモリ使用量はオブジェクトのサイズに依存しない。
+    /// 二番目以降の断片は、最初の断片と同じバージョンのオブジェクトから取得される。
+    ///
+    /// オブジェクトが存在しない場合には、要素を一つも返さずにストリームが終了する。
+    /// 空のオブジェクトの場合には、データが空の断片が一つだけ返される。
+    ///
+    /// オブジェクトにチェックサムが付与されている場合には、受信した断片から逐次的に計算して検証する。
+    /// 一致しない場合には、最後の断片の代わりに`ErrorKind::ChecksumMismatch`が返される。
+    ///
+    /// `chunk_timeout`は、断片毎の要求(再送や再試行を含む)に個別に適用される。
+    ///
+    /// `chunk_size`が`0`の場合には、要求を送信せずに`ErrorKind::InvalidInput`が返される。
+    pub fn get_object_stream(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        chunk_timeout: Duration,
+        expect: Expect,
+        consistency: ReadConsistency,
+        chunk_size: u32,
+    ) -> impl Stream<Item = ObjectChunk, Error = Error> {
+        GetObjectStream {
+            client: self.clone(),
+            bucket_id,
+            object_id,
+            chunk_timeout,
+            expect,
+            consistency,
+            chunk_size,
+            offset: 0,
+            object_size: None,
+            checksum: None,
+            future: None,
+        }
     }
 
     /// `ListObjectsRpc`を実行する。
@@ -61,13 +298,105 @@ impl Client {
         bucket_id: BucketId,
         segment: u16,
         consistency: ReadConsistency,
+        deadline: Deadline,
     ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
         let request = frugalos::ListObjectsRequest {
             bucket_id,
             segment,
             consistency,
         };
-        Response(frugalos::ListObjectsRpc::client(&self.rpc_service).call(self.server, request))
+        Call::<frugalos::ListObjectsRpc, _>::read(self, deadline, request)
+    }
+
+    /// バケツ内の全てのセグメントに対して`ListObjectsRpc`を実行し、結果をまとめて返す。
+    ///
+    /// バケツのセグメント数は`config`経由で取得される。
+    /// 同時に問い合わせるセグメントの数は最大で`concurrency`となる。
+    /// セグメント間でのオブジェクトの順序は保証されない。
+    ///
+    /// `concurrency`が`0`の場合には、要求を送信せずに`ErrorKind::InvalidInput`が返される。
+    pub fn list_all_objects(
+        &self,
+        config: &ConfigClient,
+        bucket_id: BucketId,
+        consistency: ReadConsistency,
+        concurrency: usize,
+        deadline: Deadline,
+    ) -> impl Stream<Item = ObjectSummary, Error = Error> {
+        if concurrency == 0 {
+            let e = ErrorKind::InvalidInput.cause("`concurrency` must be greater than 0");
+            return futures::future::Either::A(futures::stream::once(Err(track!(Error::from(e)))));
+        }
+
+        let client = self.clone();
+        let summaries = config
+            .get_bucket(bucket_id.clone(), deadline)
+            .and_then(move |bucket| {
+                let bucket = track_assert_some!(
+                    bucket,
+                    ErrorKind::InvalidInput,
+                    "No such bucket: {:?}",
+                    bucket_id
+                );
+                let segments = futures::stream::iter_ok(0..bucket.segment_count());
+                let summaries = segments
+                    .map(move |segment| {
+                        let request = frugalos::ListObjectsRequest {
+                            bucket_id: bucket_id.clone(),
+                            segment,
+                            consistency: consistency.clone(),
+                        };
+                        Call::<frugalos::ListObjectsRpc, _>::read(&client, deadline, request)
+                    })
+                    .buffer_unordered(concurrency)
+                    .map(futures::stream::iter_ok)
+                    .flatten();
+                Ok(summaries)
+            })
+            .flatten_stream();
+        futures::future::Either::B(summaries)
+    }
+
+    /// `ListObjectsPageRpc`を実行する。
+    pub fn list_objects_page(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        consistency: ReadConsistency,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Future<Item = ObjectSummaryPage, Error = Error> {
+        let request = frugalos::ListObjectsPageRequest {
+            bucket_id,
+            segment,
+            consistency,
+            start_after,
+            max_count,
+        };
+        Call::<frugalos::ListObjectsPageRpc, _>::read(self, deadline, request)
+    }
+
+    /// `ListObjectsPageRpc`を繰り返し実行して、セグメント内のオブジェクト一覧をページ単位で返す。
+    pub fn list_objects_pages(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Stream<Item = Vec<ObjectSummary>, Error = Error> {
+        let client = self.clone();
+        Pages::new(move |start_after| {
+            let request = frugalos::ListObjectsPageRequest {
+                bucket_id: bucket_id.clone(),
+                segment,
+                consistency: consistency.clone(),
+                start_after,
+                max_count,
+            };
+            Call::<frugalos::ListObjectsPageRpc, _>::read(&client, deadline, request)
+        })
     }
 
     /// `ListObjectsByPrefixRpc`を実行する。
@@ -75,16 +404,88 @@ impl Client {
         &self,
         bucket_id: BucketId,
         prefix: ObjectPrefix,
-        deadline: Duration,
+        deadline: Deadline,
     ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
         let request = frugalos::PrefixRequest {
             bucket_id,
             prefix,
-            deadline,
+            deadline: deadline.remaining(),
         };
-        Response(
-            frugalos::ListObjectsByPrefixRpc::client(&self.rpc_service).call(self.server, request),
-        )
+        Call::<frugalos::ListObjectsByPrefixRpc, _>::read(self, deadline, request)
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を実行する。
+    pub fn list_objects_by_prefix_page(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+    ) -> impl Future<Item = ObjectSummaryPage, Error = Error> {
+        let request = frugalos::PrefixPageRequest {
+            bucket_id,
+            prefix,
+            deadline: deadline.remaining(),
+            start_after,
+            max_count,
+        };
+        Call::<frugalos::ListObjectsByPrefixPageRpc, _>::read(self, deadline, request)
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を繰り返し実行して、
+    /// 接頭辞に一致するオブジェクトの一覧をページ単位で返す。
+    pub fn list_objects_by_prefix_pages(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+        max_count: u32,
+    ) -> impl Stream<Item = Vec<ObjectSummary>, Error = Error> {
+        let client = self.clone();
+        Pages::new(move |start_after| {
+            let request = frugalos::PrefixPageRequest {
+                bucket_id: bucket_id.clone(),
+                prefix: prefix.clone(),
+                deadline: deadline.remaining(),
+                start_after,
+                max_count,
+            };
+            Call::<frugalos::ListObjectsByPrefixPageRpc, _>::read(&client, deadline, request)
+        })
+    }
+
+    /// `ListExpiringObjectsRpc`を実行する。
+    pub fn list_expiring_objects(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        before: UnixTimestamp,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
+        let request = frugalos::ExpiringObjectsRequest {
+            bucket_id,
+            segment,
+            consistency,
+            before,
+        };
+        Call::<frugalos::ListExpiringObjectsRpc, _>::read(self, deadline, request)
+    }
+
+    /// `ListObjectsByAttributeRpc`を実行する。
+    pub fn list_objects_by_attribute(
+        &self,
+        bucket_id: BucketId,
+        filter: AttributeFilter,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
+        let request = frugalos::AttributeRequest {
+            bucket_id,
+            filter,
+            deadline: deadline.remaining(),
+        };
+        Call::<frugalos::ListObjectsByAttributeRpc, _>::read(self, deadline, request)
     }
 
     /// `GetLatestVersionRpc`を実行する。
@@ -92,11 +493,28 @@ impl Client {
         &self,
         bucket_id: BucketId,
         segment: u16,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<ObjectSummary>, Error = Error> {
         let request = frugalos::SegmentRequest { bucket_id, segment };
-        Response(
-            frugalos::GetLatestVersionRpc::client(&self.rpc_service).call(self.server, request),
-        )
+        Call::<frugalos::GetLatestVersionRpc, _>::read(self, deadline, request)
+    }
+
+    /// `WatchObjectsRpc`を実行する。
+    pub fn watch_objects(
+        &self,
+        bucket_id: BucketId,
+        since: BTreeMap<u16, ObjectVersion>,
+        max_count: u32,
+        timeout: Duration,
+        deadline: Deadline,
+    ) -> impl Future<Item = BucketEvents, Error = Error> {
+        let request = frugalos::WatchObjectsRequest {
+            bucket_id,
+            since,
+            max_count,
+            timeout,
+        };
+        Call::<frugalos::WatchObjectsRpc, _>::read(self, deadline, request)
     }
 
     /// `CountFragmentsRpc`を実行する。
@@ -104,39 +522,41 @@ impl Client {
         &self,
         bucket_id: BucketId,
         object_id: ObjectId,
-        deadline: Duration,
+        deadline: Deadline,
         expect: Expect,
         consistency: ReadConsistency,
     ) -> impl Future<Item = Option<FragmentsSummary>, Error = Error> {
         let request = frugalos::CountFragmentsRequest {
             bucket_id,
             object_id,
-            deadline,
+            deadline: deadline.remaining(),
             expect,
             consistency,
         };
-        Response(frugalos::CountFragmentsRpc::client(&self.rpc_service).call(self.server, request))
+        Call::<frugalos::CountFragmentsRpc, _>::read(self, deadline, request)
     }
 
-    /// `HeadObjectRpc`を実行する。
+    /// `HeadObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、応答の遅いサーバに加えて別のサーバにも要求が送信される。
     pub fn head_object(
         &self,
         bucket_id: BucketId,
         object_id: ObjectId,
-        deadline: Duration,
+        deadline: Deadline,
         expect: Expect,
         consistency: ReadConsistency,
         check_storage: bool,
-    ) -> impl Future<Item = Option<ObjectVersion>, Error = Error> {
+    ) -> impl Future<Item = Option<ObjectInfo>, Error = Error> {
         let request = frugalos::HeadObjectRequest {
             bucket_id,
             object_id,
-            deadline,
+            deadline: deadline.remaining(),
             expect,
             consistency,
             check_storage,
         };
-        Response(frugalos::HeadObjectRpc::client(&self.rpc_service).call(self.server, request))
+        self.hedged_call::<frugalos::HeadObjectInfoRpc, _>(deadline, request)
     }
 
     /// `PutObjectRpc`を実行する。
@@ -145,7 +565,7 @@ impl Client {
         bucket_id: BucketId,
         object_id: ObjectId,
         content: Vec<u8>,
-        deadline: Duration,
+        deadline: Deadline,
         expect: Expect,
         multiplicity_config: MultiplicityConfig,
     ) -> impl Future<Item = (ObjectVersion, bool), Error = Error> {
@@ -153,11 +573,89 @@ impl Client {
             bucket_id,
             object_id,
             content,
-            deadline,
+            deadline: deadline.remaining(),
             expect,
             multiplicity_config,
         };
-        Response(frugalos::PutObjectRpc::client(&self.rpc_service).call(self.server, request))
+        Call::<frugalos::PutObjectRpc, _>::new(self, deadline, request)
+    }
+
+    /// `PutObjectWithOptionsRpc`を実行する。
+    ///
+    /// オプションは`PutObjectRpc`とは別の手続きで送信されるため、
+    /// これをサポートしないサーバに対しては、チェックサム等が無視されることなくエラーとなる。
+    pub fn put_object_with_options(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        content: Vec<u8>,
+        deadline: Deadline,
+        expect: Expect,
+        options: PutObjectOptions,
+    ) -> impl Future<Item = (ObjectVersion, bool), Error = Error> {
+        let checksum = options
+            .checksum
+            .map(|algorithm| Checksum::calculate(algorithm, &content));
+        let request = frugalos::PutObjectWithOptionsRequest {
+            bucket_id,
+            object_id,
+            content,
+            deadline: deadline.remaining(),
+            expect,
+            multiplicity_config: options.multiplicity_config,
+            expiry: options.expiry,
+            attributes: options.attributes,
+            checksum,
+        };
+        Call::<frugalos::PutObjectWithOptionsRpc, _>::new(self, deadline, request)
+    }
+
+    /// 分割アップロード用のRPC群を実行して、`content`の内容をオブジェクトとして保存する。
+    ///
+    /// `content`が返すバイト列が、そのまま一つの断片としてサーバに送信される。
+    /// 送信中の断片は常に一つだけなので、メモリ使用量は断片のサイズに比例する。
+    ///
+    /// `chunk_timeout`は、アップロードの開始・完了および断片毎の要求に個別に適用される。
+    ///
+    /// 途中でエラーが発生した場合や、完了前に`Future`が破棄された場合には、アップロードは中断される。
+    pub fn put_object_stream<S>(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        content: S,
+        chunk_timeout: Duration,
+        expect: Expect,
+        options: PutObjectOptions,
+    ) -> impl Future<Item = (ObjectVersion, bool), Error = Error>
+    where
+        S: Stream<Item = Vec<u8>, Error = Error>,
+    {
+        let request = frugalos::StartPutObjectRequest {
+            bucket_id,
+            object_id,
+            deadline: chunk_timeout,
+            expect,
+            multiplicity_config: options.multiplicity_config,
+            expiry: options.expiry,
+            attributes: options.attributes,
+        };
+        let client = self.pinned(self.select_server());
+        let start = Call::<frugalos::StartPutObjectRpc, _>::new(
+            &client,
+            Deadline::new(chunk_timeout),
+            request,
+        );
+        PutObjectStream {
+            client,
+            chunk_timeout,
+            content,
+            start: Some(start),
+            upload_id: None,
+            hasher: options.checksum.map(ChecksumHasher::new),
+            chunk: None,
+            complete: None,
+            offset: 0,
+        }
     }
 
     /// `DeleteObjectRpc`を実行する。
@@ -165,17 +663,119 @@ impl Client {
         &self,
         bucket_id: BucketId,
         object_id: ObjectId,
-        deadline: Duration,
+        deadline: Deadline,
         expect: Expect,
     ) -> impl Future<Item = Option<ObjectVersion>, Error = Error> {
         let request = frugalos::ObjectRequest {
             bucket_id,
             object_id,
-            deadline,
+            deadline: deadline.remaining(),
             expect,
             consistency: None,
         };
-        Response(frugalos::DeleteObjectRpc::client(&self.rpc_service).call(self.server, request))
+        Call::<frugalos::DeleteObjectRpc, _>::new(self, deadline, request)
+    }
+
+    /// `BatchPutObjectsRpc`を実行する。
+    ///
+    /// 結果は`objects`と同じ順序で、オブジェクト毎に返される。
+    pub fn batch_put_objects(
+        &self,
+        bucket_id: BucketId,
+        objects: Vec<PutObjectItem>,
+        deadline: Deadline,
+        multiplicity_config: MultiplicityConfig,
+    ) -> impl Future<Item = Vec<Result<(ObjectVersion, bool)>>, Error = Error> {
+        let request = frugalos::BatchPutObjectsRequest {
+            bucket_id,
+            objects,
+            deadline: deadline.remaining(),
+            multiplicity_config,
+        };
+        Call::<frugalos::BatchPutObjectsRpc, _>::new(self, deadline, request)
+    }
+
+    /// `BatchGetObjectsRpc`を実行する。
+    ///
+    /// 結果は`objects`と同じ順序で、オブジェクト毎に返される。
+    /// チェックサムが一致しないオブジェクトの結果は`ErrorKind::ChecksumMismatch`となる。
+    #[allow(clippy::type_complexity)]
+    pub fn batch_get_objects(
+        &self,
+        bucket_id: BucketId,
+        objects: Vec<ObjectItem>,
+        deadline: Deadline,
+        consistency: ReadConsistency,
+    ) -> impl Future<Item = Vec<Result<Option<(ObjectInfo, Vec<u8>)>>>, Error = Error> {
+        let request = frugalos::BatchGetObjectsRequest {
+            bucket_id,
+            objects,
+            deadline: deadline.remaining(),
+            consistency,
+        };
+        Call::<frugalos::BatchGetObjectsRpc, _>::read(self, deadline, request).map(|results| {
+            results
+                .into_iter()
+                .map(|result| match result {
+                    Ok(Some((info, content))) => {
+                        track!(verify_content(&info, &content))?;
+                        Ok(Some((info, content)))
+                    }
+                    _ => result,
+                })
+                .collect()
+        })
+    }
+
+    /// `BatchDeleteObjectsRpc`を実行する。
+    ///
+    /// 結果は`objects`と同じ順序で、オブジェクト毎に返される。
+    pub fn batch_delete_objects(
+        &self,
+        bucket_id: BucketId,
+        objects: Vec<ObjectItem>,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<Result<Option<ObjectVersion>>>, Error = Error> {
+        let request = frugalos::BatchDeleteObjectsRequest {
+            bucket_id,
+            objects,
+            deadline: deadline.remaining(),
+        };
+        Call::<frugalos::BatchDeleteObjectsRpc, _>::new(self, deadline, request)
+    }
+
+    /// `CopyObjectRpc`を実行する。
+    pub fn copy_object(
+        &self,
+        source: ObjectTarget,
+        destination: ObjectTarget,
+        deadline: Deadline,
+        multiplicity_config: MultiplicityConfig,
+    ) -> impl Future<Item = Option<(ObjectVersion, bool)>, Error = Error> {
+        let request = frugalos::CopyObjectRequest {
+            source,
+            destination,
+            deadline: deadline.remaining(),
+            multiplicity_config,
+        };
+        Call::<frugalos::CopyObjectRpc, _>::new(self, deadline, request)
+    }
+
+    /// `RenameObjectRpc`を実行する。
+    pub fn rename_object(
+        &self,
+        source: ObjectTarget,
+        destination: ObjectTarget,
+        deadline: Deadline,
+        multiplicity_config: MultiplicityConfig,
+    ) -> impl Future<Item = Option<(ObjectVersion, bool)>, Error = Error> {
+        let request = frugalos::CopyObjectRequest {
+            source,
+            destination,
+            deadline: deadline.remaining(),
+            multiplicity_config,
+        };
+        Call::<frugalos::RenameObjectRpc, _>::new(self, deadline, request)
     }
 
     /// `DeleteObjectByVersionRpc`を実行する。
@@ -184,18 +784,62 @@ impl Client {
         bucket_id: BucketId,
         segment: u16,
         object_version: ObjectVersion,
-        deadline: Duration,
+        deadline: Deadline,
     ) -> impl Future<Item = Option<ObjectVersion>, Error = Error> {
         let request = frugalos::VersionRequest {
             bucket_id,
             segment,
             object_version,
-            deadline,
+            deadline: deadline.remaining(),
         };
-        Response(
-            frugalos::DeleteObjectByVersionRpc::client(&self.rpc_service)
-                .call(self.server, request),
-        )
+        Call::<frugalos::DeleteObjectByVersionRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListObjectsByRangeRpc`を実行する。
+    pub fn list_objects_by_range(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
+        let request = frugalos::RangePageRequest {
+            bucket_id,
+            segment,
+            targets,
+            consistency,
+            max_count,
+        };
+        Call::<frugalos::ListObjectsByRangeRpc, _>::read(self, deadline, request)
+    }
+
+    /// `ListObjectsByRangeRpc`を繰り返し実行して、
+    /// 指定範囲のバージョンを持つオブジェクトの一覧を、バージョンの昇順にページ単位で返す。
+    pub fn list_objects_by_range_pages(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Stream<Item = Vec<ObjectSummary>, Error = Error> {
+        let client = self.clone();
+        let Range { start, end } = targets;
+        Pages::new(move |next| {
+            let request = frugalos::RangePageRequest {
+                bucket_id: bucket_id.clone(),
+                segment,
+                targets: next.unwrap_or(start)..end,
+                consistency: consistency.clone(),
+                max_count,
+            };
+            Call::<frugalos::ListObjectsByRangeRpc, _>::read(&client, deadline, request)
+                .map(move |summaries| range_page(summaries, max_count, end))
+        })
+        .filter(|summaries| !summaries.is_empty())
     }
 
     /// `DeleteObjectsByRangeRpc`を実行する。
@@ -204,17 +848,15 @@ impl Client {
         bucket_id: BucketId,
         segment: u16,
         targets: Range<ObjectVersion>,
-        deadline: Duration,
+        deadline: Deadline,
     ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
         let request = frugalos::RangeRequest {
             bucket_id,
             segment,
             targets,
-            deadline,
+            deadline: deadline.remaining(),
         };
-        Response(
-            frugalos::DeleteObjectsByRangeRpc::client(&self.rpc_service).call(self.server, request),
-        )
+        Call::<frugalos::DeleteObjectsByRangeRpc, _>::new(self, deadline, request)
     }
 
     /// オブジェクトを ID のプレフィックスを指定して削除する。
@@ -222,17 +864,94 @@ impl Client {
         &self,
         bucket_id: BucketId,
         prefix: ObjectPrefix,
-        deadline: Duration,
+        deadline: Deadline,
     ) -> impl Future<Item = DeleteObjectsByPrefixSummary, Error = Error> {
         let request = frugalos::PrefixRequest {
             bucket_id,
             prefix,
-            deadline,
+            deadline: deadline.remaining(),
         };
-        Response(
-            frugalos::DeleteObjectsByPrefixRpc::client(&self.rpc_service)
-                .call(self.server, request),
-        )
+        Call::<frugalos::DeleteObjectsByPrefixRpc, _>::new(self, deadline, request)
+    }
+
+    /// `StartDeleteObjectsByPrefixRpc`を実行する。
+    ///
+    /// 削除処理の完了を待たずにジョブのハンドルを返す。
+    /// 進捗は`delete_by_prefix_progress`で取得できる。
+    ///
+    /// ジョブはそれを開始したサーバ上で管理されるため、
+    /// ハンドルを指定した以降の要求は、負荷分散の設定に依らず常にそのサーバに送信される。
+    pub fn start_delete_by_prefix(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> impl Future<Item = JobHandle, Error = Error> {
+        let request = frugalos::PrefixRequest {
+            bucket_id,
+            prefix,
+            deadline: deadline.remaining(),
+        };
+        let server = self.select_server();
+        let client = self.pinned(server);
+        Call::<frugalos::StartDeleteObjectsByPrefixRpc, _>::new(&client, deadline, request)
+            .map(move |id| JobHandle { server, id })
+    }
+
+    /// `GetDeleteObjectsByPrefixProgressRpc`を、ジョブを開始したサーバに対して実行する。
+    pub fn delete_by_prefix_progress(
+        &self,
+        job: JobHandle,
+        deadline: Deadline,
+    ) -> impl Future<Item = Option<DeleteObjectsByPrefixProgress>, Error = Error> {
+        let client = self.pinned(job.server);
+        Call::<frugalos::GetDeleteObjectsByPrefixProgressRpc, _>::read(&client, deadline, job.id)
+    }
+
+    /// `CancelDeleteObjectsByPrefixRpc`を、ジョブを開始したサーバに対して実行する。
+    pub fn cancel_delete_by_prefix(
+        &self,
+        job: JobHandle,
+        deadline: Deadline,
+    ) -> impl Future<Item = Option<DeleteObjectsByPrefixProgress>, Error = Error> {
+        let client = self.pinned(job.server);
+        Call::<frugalos::CancelDeleteObjectsByPrefixRpc, _>::new(&client, deadline, job.id)
+    }
+
+    /// `PreviewDeleteObjectsByPrefixRpc`を実行する。
+    ///
+    /// `delete_by_prefix`で削除されるオブジェクトの数を返す。オブジェクトは削除されない。
+    pub fn preview_delete_by_prefix(
+        &self,
+        bucket_id: BucketId,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> impl Future<Item = DeleteObjectsByPrefixSummary, Error = Error> {
+        let request = frugalos::PrefixRequest {
+            bucket_id,
+            prefix,
+            deadline: deadline.remaining(),
+        };
+        Call::<frugalos::PreviewDeleteObjectsByPrefixRpc, _>::read(self, deadline, request)
+    }
+
+    /// `PreviewDeleteObjectsByRangeRpc`を実行する。
+    ///
+    /// `delete_by_range`で削除されるオブジェクトの一覧を返す。オブジェクトは削除されない。
+    pub fn preview_delete_by_range(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        targets: Range<ObjectVersion>,
+        deadline: Deadline,
+    ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
+        let request = frugalos::RangeRequest {
+            bucket_id,
+            segment,
+            targets,
+            deadline: deadline.remaining(),
+        };
+        Call::<frugalos::PreviewDeleteObjectsByRangeRpc, _>::read(self, deadline, request)
     }
 
     /// Executes `DeleteObjectSetFromDeviceRpc`.
@@ -241,37 +960,562 @@ impl Client {
         bucket_id: BucketId,
         device_id: DeviceId,
         object_ids: BTreeSet<ObjectId>,
+        deadline: Deadline,
     ) -> impl Future<Item = (), Error = Error> {
-        Response(
-            frugalos::DeleteObjectSetFromDeviceRpc::client(&self.rpc_service).call(
-                self.server,
-                frugalos::DeleteObjectSetFromDeviceRequest {
-                    bucket_id,
-                    device_id,
-                    object_ids,
-                },
-            ),
+        Call::<frugalos::DeleteObjectSetFromDeviceRpc, _>::new(
+            self,
+            deadline,
+            frugalos::DeleteObjectSetFromDeviceRequest {
+                bucket_id,
+                device_id,
+                object_ids,
+            },
+        )
+    }
+
+    /// Executes `PreviewDeleteObjectSetFromDeviceRpc`.
+    ///
+    /// Returns the objects that `delete_from_device_by_object_ids` would delete.
+    /// Nothing is deleted.
+    pub fn preview_delete_from_device_by_object_ids(
+        &self,
+        bucket_id: BucketId,
+        device_id: DeviceId,
+        object_ids: BTreeSet<ObjectId>,
+        deadline: Deadline,
+    ) -> impl Future<Item = BTreeSet<ObjectId>, Error = Error> {
+        Call::<frugalos::PreviewDeleteObjectSetFromDeviceRpc, _>::read(
+            self,
+            deadline,
+            frugalos::DeleteObjectSetFromDeviceRequest {
+                bucket_id,
+                device_id,
+                object_ids,
+            },
         )
     }
 
     /// `StopRpc`を実行する。
-    pub fn stop(&self) -> impl Future<Item = (), Error = Error> {
-        Response(frugalos::StopRpc::client(&self.rpc_service).call(self.server, ()))
+    pub fn stop(&self, deadline: Deadline) -> impl Future<Item = (), Error = Error> {
+        Call::<frugalos::StopRpc, _>::new(self, deadline, ())
     }
 
     /// `TakeSnapshotRpc`を実行する。
-    pub fn take_snapshot(&self) -> impl Future<Item = (), Error = Error> {
-        Response(frugalos::TakeSnapshotRpc::client(&self.rpc_service).call(self.server, ()))
+    pub fn take_snapshot(&self, deadline: Deadline) -> impl Future<Item = (), Error = Error> {
+        Call::<frugalos::TakeSnapshotRpc, _>::new(self, deadline, ())
     }
 
     /// Executes `SetRepairConfigRpc`
     pub fn set_repair_config(
         &self,
         repair_config: RepairConfig,
+        deadline: Deadline,
     ) -> impl Future<Item = (), Error = Error> {
-        Response(
-            frugalos::SetRepairConfigRpc::client(&self.rpc_service)
-                .call(self.server, repair_config),
-        )
+        Call::<frugalos::SetRepairConfigRpc, _>::new(self, deadline, repair_config)
+    }
+}
+
+fn verify_content(info: &ObjectInfo, content: &[u8]) -> Result<()> {
+    if let Some(ref checksum) = info.checksum {
+        track!(checksum.verify(content))?;
+    }
+    Ok(())
+}
+
+/// オブジェクト保存時に指定可能なオプション。
+#[derive(Debug, Clone, Default)]
+pub struct PutObjectOptions {
+    /// 多重度に関する設定。
+    pub multiplicity_config: MultiplicityConfig,
+
+    /// オブジェクトの有効期限。
+    pub expiry: Option<Expiry>,
+
+    /// ユーザ定義の属性群。
+    pub attributes: Attributes,
+
+    /// オブジェクトの内容のチェックサムの計算アルゴリズム。
+    ///
+    /// 指定された場合には、クライアント側で計算したチェックサムがサーバに送信され、検証される。
+    pub checksum: Option<ChecksumAlgorithm>,
+}
+
+/// サーバ側で非同期に実行されるジョブのハンドル。
+///
+/// ジョブのIDに加えて、ジョブを開始したサーバのアドレスを保持する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JobHandle {
+    server: SocketAddr,
+    id: JobId,
+}
+impl JobHandle {
+    /// 新しい`JobHandle`インスタンスを生成する。
+    ///
+    /// `server`には、ジョブを開始したサーバのアドレスを指定すること。
+    pub fn new(server: SocketAddr, id: JobId) -> Self {
+        JobHandle { server, id }
+    }
+
+    /// ジョブを開始したサーバのアドレスを返す。
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// ジョブのIDを返す。
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+}
+
+/// `Read`から読み込んだデータを、固定長の断片として返す`Stream`。
+///
+/// `Client::put_object_stream`にファイル等の内容を渡すために使用する。
+///
+/// ブロッキングな読み込みでエクゼキュータのスレッドを占有しないように、読み込みは専用のスレッドで行われる。
+/// 先読みされる断片は一つだけなので、メモリ使用量は断片のサイズに比例する。
+/// インスタンスが破棄されると、読み込み用のスレッドも終了する。
+#[derive(Debug)]
+pub struct ChunkReader {
+    rx: fibers_mpsc::Receiver<Result<Vec<u8>>>,
+    credit_tx: std_mpsc::Sender<()>,
+}
+impl ChunkReader {
+    /// 新しい`ChunkReader`インスタンスを生成する。
+    ///
+    /// `chunk_size`が`0`の場合には`ErrorKind::InvalidInput`が返される。
+    pub fn new<R>(inner: R, chunk_size: usize) -> Result<Self>
+    where
+        R: Read + Send + 'static,
+    {
+        track_assert_ne!(chunk_size, 0, ErrorKind::InvalidInput);
+
+        let (tx, rx) = fibers_mpsc::channel();
+        let (credit_tx, credit_rx) = std_mpsc::channel();
+        thread::spawn(move || {
+            let mut inner = inner;
+            loop {
+                let mut chunk = Vec::with_capacity(chunk_size);
+                if let Err(e) = (&mut inner).take(chunk_size as u64).read_to_end(&mut chunk) {
+                    let _ = tx.send(Err(track!(Error::from(e))));
+                    return;
+                }
+                let eos = chunk.len() < chunk_size;
+                if !chunk.is_empty() && tx.send(Ok(chunk)).is_err() {
+                    return;
+                }
+
+                // NOTE: 送信した断片が受信側に取り出されるまで、次の読み込みを待機する
+                if eos || credit_rx.recv().is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(ChunkReader { rx, credit_tx })
+    }
+}
+impl Stream for ChunkReader {
+    type Item = Vec<u8>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.rx.poll().expect("Never fails") {
+            Async::NotReady => Ok(Async::NotReady),
+            Async::Ready(None) => Ok(Async::Ready(None)),
+            Async::Ready(Some(chunk)) => {
+                let chunk = track!(chunk)?;
+                let _ = self.credit_tx.send(());
+                Ok(Async::Ready(Some(chunk)))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PutObjectStream<S> {
+    client: Client,
+    chunk_timeout: Duration,
+    content: S,
+    start: Option<Call<frugalos::StartPutObjectRpc, UploadId>>,
+    upload_id: Option<UploadId>,
+    hasher: Option<ChecksumHasher>,
+    chunk: Option<Call<frugalos::PutObjectChunkRpc, ()>>,
+    complete: Option<Call<frugalos::CompletePutObjectRpc, (ObjectVersion, bool)>>,
+    offset: u64,
+}
+impl<S> PutObjectStream<S>
+where
+    S: Stream<Item = Vec<u8>, Error = Error>,
+{
+    fn poll_upload(&mut self) -> Poll<(ObjectVersion, bool), Error> {
+        if let Async::Ready(Some(upload_id)) = track!(self.start.poll())? {
+            self.start = None;
+            self.upload_id = Some(upload_id);
+        }
+        let upload_id = if let Some(upload_id) = self.upload_id {
+            upload_id
+        } else {
+            return Ok(Async::NotReady);
+        };
+
+        loop {
+            if let Some(ref mut complete) = self.complete {
+                if let Async::Ready(result) = track!(complete.poll())? {
+                    self.upload_id = None;
+                    return Ok(Async::Ready(result));
+                }
+                return Ok(Async::NotReady);
+            }
+            if let Async::NotReady = track!(self.chunk.poll())? {
+                return Ok(Async::NotReady);
+            }
+            self.chunk = None;
+
+            match track!(self.content.poll())? {
+                Async::NotReady => return Ok(Async::NotReady),
+                Async::Ready(None) => {
+                    let request = frugalos::CompletePutObjectRequest {
+                        upload_id,
+                        object_size: self.offset,
+                        checksum: self.hasher.take().map(ChecksumHasher::finish),
+                    };
+                    let deadline = Deadline::new(self.chunk_timeout);
+                    let future = Call::new(&self.client, deadline, request);
+                    self.complete = Some(future);
+                }
+                Async::Ready(Some(data)) => {
+                    if data.is_empty() {
+                        continue;
+                    }
+                    let offset = self.offset;
+                    self.offset += data.len() as u64;
+                    if let Some(ref mut hasher) = self.hasher {
+                        hasher.update(&data);
+                    }
+                    let request = frugalos::PutObjectChunkRequest {
+                        upload_id,
+                        offset,
+                        data,
+                    };
+                    let deadline = Deadline::new(self.chunk_timeout);
+                    let future = Call::new(&self.client, deadline, request);
+                    self.chunk = Some(future);
+                }
+            }
+        }
+    }
+}
+impl<S> PutObjectStream<S> {
+    fn abort(&mut self) {
+        if let Some(upload_id) = self.upload_id.take() {
+            let _ = frugalos::AbortPutObjectRpc::client(&self.client.rpc_service)
+                .cast(self.client.select_server(), upload_id);
+        }
+    }
+}
+impl<S> Future for PutObjectStream<S>
+where
+    S: Stream<Item = Vec<u8>, Error = Error>,
+{
+    type Item = (ObjectVersion, bool);
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let result = self.poll_upload();
+        if result.is_err() {
+            self.abort();
+        }
+        result
+    }
+}
+impl<S> Drop for PutObjectStream<S> {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+#[derive(Debug)]
+struct GetObjectStream {
+    client: Client,
+    bucket_id: BucketId,
+    object_id: ObjectId,
+    chunk_timeout: Duration,
+    expect: Expect,
+    consistency: ReadConsistency,
+    chunk_size: u32,
+    offset: u64,
+    object_size: Option<u64>,
+    checksum: Option<(Checksum, ChecksumHasher)>,
+    future: Option<Call<frugalos::GetObjectChunkRpc, Option<ObjectChunk>>>,
+}
+impl GetObjectStream {
+    fn is_finished(&self) -> bool {
+        match self.object_size {
+            Some(size) => self.offset >= size,
+            None => false,
+        }
+    }
+}
+impl Stream for GetObjectStream {
+    type Item = ObjectChunk;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        track_assert_ne!(self.chunk_size, 0, ErrorKind::InvalidInput);
+        if self.future.is_none() {
+            if self.is_finished() {
+                return Ok(Async::Ready(None));
+            }
+            let request = frugalos::ObjectChunkRequest {
+                bucket_id: self.bucket_id.clone(),
+                object_id: self.object_id.clone(),
+                deadline: self.chunk_timeout,
+                expect: self.expect.clone(),
+                consistency: self.consistency.clone(),
+                offset: self.offset,
+                max_size: self.chunk_size,
+            };
+            let future = Call::read(&self.client, Deadline::new(self.chunk_timeout), request);
+            self.future = Some(future);
+        }
+
+        let chunk = match track!(self.future.poll())? {
+            Async::Ready(Some(chunk)) => chunk,
+            _ => return Ok(Async::NotReady),
+        };
+        self.future = None;
+        match chunk {
+            None => {
+                track_assert!(self.object_size.is_none(), ErrorKind::Unexpected(None));
+                self.object_size = Some(0);
+                Ok(Async::Ready(None))
+            }
+            Some(chunk) => {
+                track_assert_eq!(chunk.offset, self.offset, ErrorKind::Other);
+                if self.object_size.is_none() {
+                    self.object_size = Some(chunk.object_size);
+                    self.expect = Expect::IfMatch(vec![chunk.version]);
+                    self.checksum = chunk
+                        .checksum
+                        .map(|c| (c, ChecksumHasher::new(c.algorithm())));
+                }
+                track_assert!(
+                    !chunk.data.is_empty() || chunk.object_size == 0,
+                    ErrorKind::Other,
+                    "Empty chunk: offset={}",
+                    chunk.offset
+                );
+                self.offset += chunk.data.len() as u64;
+                if let Some((_, ref mut hasher)) = self.checksum {
+                    hasher.update(&chunk.data);
+                }
+                if self.is_finished() {
+                    if let Some((expected, hasher)) = self.checksum.take() {
+                        track_assert_eq!(hasher.finish(), expected, ErrorKind::ChecksumMismatch);
+                    }
+                }
+                Ok(Async::Ready(Some(chunk)))
+            }
+        }
+    }
+}
+
+/// 要求に含まれるサーバ側のデッドラインを、送信直前の残り時間で更新するためのトレイト。
+///
+/// デッドラインを含まない要求では何も行わない。
+trait SetDeadline {
+    fn set_deadline(&mut self, _deadline: Duration) {}
+}
+/// `deadline`フィールドを持つ要求に`SetDeadline`を実装する。
+macro_rules! impl_set_deadline {
+    ($($request:ty),*) => {
+        $(
+            impl SetDeadline for $request {
+                fn set_deadline(&mut self, deadline: Duration) {
+                    self.deadline = deadline;
+                }
+            }
+        )*
+    };
+}
+impl_set_deadline!(
+    frugalos::AttributeRequest,
+    frugalos::BatchDeleteObjectsRequest,
+    frugalos::BatchGetObjectsRequest,
+    frugalos::BatchPutObjectsRequest,
+    frugalos::CopyObjectRequest,
+    frugalos::CountFragmentsRequest,
+    frugalos::HeadObjectRequest,
+    frugalos::ObjectChunkRequest,
+    frugalos::ObjectRangeRequest,
+    frugalos::ObjectRequest,
+    frugalos::PrefixPageRequest,
+    frugalos::PrefixRequest,
+    frugalos::PutObjectRequest,
+    frugalos::PutObjectWithOptionsRequest,
+    frugalos::RangeRequest,
+    frugalos::StartPutObjectRequest,
+    frugalos::VersionRequest
+);
+
+impl SetDeadline for () {}
+impl SetDeadline for JobId {}
+impl SetDeadline for RepairConfig {}
+impl SetDeadline for frugalos::CompletePutObjectRequest {}
+impl SetDeadline for frugalos::DeleteObjectSetFromDeviceRequest {}
+impl SetDeadline for frugalos::ExpiringObjectsRequest {}
+impl SetDeadline for frugalos::ListObjectsPageRequest {}
+impl SetDeadline for frugalos::ListObjectsRequest {}
+impl SetDeadline for frugalos::PutObjectChunkRequest {}
+impl SetDeadline for frugalos::RangePageRequest {}
+impl SetDeadline for frugalos::SegmentRequest {}
+impl SetDeadline for frugalos::WatchObjectsRequest {}
+
+#[derive(Debug)]
+struct Call<T: RpcCall, U> {
+    client: Client,
+    request: Option<T::Req>,
+    response: Option<(Outstanding, Guarded<Response<U>>)>,
+    tried_servers: Vec<SocketAddr>,
+    deadline: Deadline,
+    retry: Retry,
+    is_read: bool,
+}
+impl<T, U> Call<T, U>
+where
+    U: Send + 'static,
+    T: RpcCall<Res = Result<U>>,
+    T::Req: Clone + SetDeadline,
+    T::ReqEncoder: Default,
+    T::ResDecoder: Default,
+{
+    /// 書き込み系のRPCを実行する。
+    ///
+    /// タイムアウトした要求はサーバ側で適用済みの可能性があるため、
+    /// 別のサーバへの再送は、接続に失敗した(i.e., `ErrorKind::Unavailable`の)場合にのみ行われる。
+    fn new(client: &Client, deadline: Deadline, request: T::Req) -> Self {
+        Self::with_kind(client, deadline, request, false)
+    }
+
+    /// 読み込み系のRPCを実行する。
+    ///
+    /// `ErrorKind::Timeout`の場合にも、別のサーバへの再送が行われる。
+    fn read(client: &Client, deadline: Deadline, request: T::Req) -> Self {
+        Self::with_kind(client, deadline, request, true)
+    }
+
+    fn with_kind(client: &Client, deadline: Deadline, request: T::Req, is_read: bool) -> Self {
+        let retry = Retry::new(client.retry_policy.clone(), deadline);
+        let may_failover = client.pinned.is_none() && client.pool.len() > 1;
+        let mut call = Call {
+            client: client.clone(),
+            request: None,
+            response: None,
+            tried_servers: Vec::new(),
+            deadline,
+            retry,
+            is_read,
+        };
+        let server = client.select_server();
+        if call.retry.may_retry() || may_failover {
+            // NOTE: 再送が行われない場合には、要求(e.g., オブジェクトの内容)を複製しない
+            call.request = Some(request.clone());
+        }
+        call.send(server, request);
+        call
+    }
+
+    fn send(&mut self, server: SocketAddr, mut request: T::Req) {
+        let outstanding = self.client.pool.start(server);
+        let rpc_service = &self.client.rpc_service;
+        let future = Guarded::new(
+            self.client.breaker.as_ref(),
+            server,
+            self.deadline,
+            |timeout| {
+                // NOTE: 再送時にも、その時点での残り時間をサーバに伝える
+                request.set_deadline(timeout);
+                Response::call::<T>(rpc_service, server, request, timeout)
+            },
+        );
+        self.tried_servers.push(server);
+        self.response = Some((outstanding, future));
+    }
+
+    /// 未送信のサーバが存在する場合には、そのサーバに要求を再送する。
+    fn failover(&mut self) -> bool {
+        if self.client.pinned.is_some() {
+            return false;
+        }
+        let server = match self.client.pool.select(&self.tried_servers) {
+            None => return false,
+            Some(server) => server,
+        };
+        let request = match self.request {
+            None => return false,
+            Some(ref request) => request.clone(),
+        };
+        self.send(server, request);
+        true
+    }
+}
+impl<T, U> Future for Call<T, U>
+where
+    U: Send + 'static,
+    T: RpcCall<Res = Result<U>>,
+    T::Req: Clone + SetDeadline,
+    T::ReqEncoder: Default,
+    T::ResDecoder: Default,
+{
+    type Item = U;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            match track!(self.retry.poll())? {
+                Async::NotReady => return Ok(Async::NotReady),
+                Async::Ready(true) => {
+                    let request = self.request.clone().expect("Never fails");
+                    let server = self.client.select_server();
+                    self.tried_servers.clear();
+                    self.send(server, request);
+                }
+                Async::Ready(false) => {}
+            }
+
+            let (server, polled) = match self.response {
+                None => return Ok(Async::NotReady),
+                Some((ref outstanding, ref mut future)) => (outstanding.server(), future.poll()),
+            };
+            match polled {
+                Err(e) => {
+                    self.response = None;
+                    track_assert!(!self.deadline.is_expired(), ErrorKind::Timeout; e, T::NAME);
+                    match *e.kind() {
+                        ErrorKind::Unavailable => {
+                            self.client.pool.record_failure(server);
+                            if self.failover() {
+                                continue;
+                            }
+                        }
+                        ErrorKind::Timeout => {
+                            self.client.pool.record_failure(server);
+                            if self.is_read && self.failover() {
+                                continue;
+                            }
+                        }
+                        _ => {}
+                    }
+                    if let Err(e) = self.retry.schedule(e) {
+                        return Err(track!(e, T::NAME));
+                    }
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(response)) => {
+                    self.response = None;
+                    self.client.pool.record_success(server);
+                    return Ok(Async::Ready(response));
+                }
+            }
+        }
     }
 }
@@ -1,30 +1,158 @@
 //! MDS(metadata store)用のRPCクライアント。
+use fibers::time::timer::{self, Timeout};
 use fibers_rpc::client::ClientServiceHandle as RpcServiceHandle;
 use fibers_rpc::{Call as RpcCall, Cast as RpcCast};
-use futures::{Async, Future, Poll};
+use futures::{Async, Future, Poll, Stream};
+use std::collections::VecDeque;
 use std::ops::Range;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use super::Response;
+use super::breaker::CircuitBreaker;
+use super::retry::RetryPolicy;
+use super::{range_page, Guarded, Hedged, Pages, Response, Retry};
 use consistency::ReadConsistency;
+use deadline::Deadline;
 use entity::node::{LocalNodeId, RemoteNodeId};
 use entity::object::{
-    DeleteObjectsByPrefixSummary, Metadata, ObjectId, ObjectPrefix, ObjectSummary, ObjectVersion,
+    AttributeFilter, Attributes, DeleteObjectsByPrefixSummary, Expiry, Metadata, ObjectEvent,
+    ObjectEvents, ObjectId, ObjectInfo, ObjectPrefix, ObjectSummary, ObjectSummaryPage,
+    ObjectVersion,
 };
 use expect::Expect;
-use schema::mds;
+use schema::mds::{self, TransactionOperation, TransactionOutcome};
+use time::UnixTimestamp;
 use {Error, ErrorKind, Result};
 
+/// 変更通知の取得に失敗した際に、再接続を試みるまでの待機時間。
+const WATCH_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 変更通知の取得時に、サーバ側の待機時間(`timeout`)に加えて許容する時間。
+const WATCH_DEADLINE_MARGIN: Duration = Duration::from_secs(5);
+
+/// `ErrorKind::NotLeader`を受けて、リーダを問い合わせ直して要求を再送する最大回数。
+const MAX_LEADER_REDIRECTS: usize = 2;
+
 /// RPCクライアント。
-#[derive(Debug)]
+///
+/// 判明したリーダノードはキャッシュされ、以降の要求はリーダに直接送信される。
+/// キャッシュは`clone`されたインスタンス間で共有される。
+///
+/// 各メソッドに指定された`deadline`は、リーダの問い合わせや再試行も含めた処理全体に適用される。
+/// デッドラインを過ぎた場合には`ErrorKind::Timeout`が返される。
+/// 複数の要求を発行するストリームでは、`deadline`は全ての要求を合わせた処理時間に対して適用される。
+#[derive(Debug, Clone)]
 pub struct Client {
     node: RemoteNodeId,
+    leader: Arc<Mutex<Option<RemoteNodeId>>>,
     rpc_service: RpcServiceHandle,
+    retry_policy: RetryPolicy,
+    hedging: Option<(Duration, Vec<RemoteNodeId>)>,
+    breaker: Option<CircuitBreaker>,
 }
 impl Client {
     /// 新しい`Client`インスタンスを生成する。
+    ///
+    /// リーダが判明するまでは、要求は`node`に送信される。
+    ///
+    /// `ErrorKind::NotLeader`が発生した場合には、再試行ポリシーに依らず、
+    /// リーダを問い合わせ直した上で最大二回まで要求が再送される。
+    ///
+    /// デフォルトの再試行ポリシーでは、それ以外のエラーによる再試行は行われない。
     pub fn new(node: RemoteNodeId, rpc_service: RpcServiceHandle) -> Self {
-        Client { node, rpc_service }
+        Client {
+            node,
+            leader: Arc::new(Mutex::new(None)),
+            rpc_service,
+            retry_policy: RetryPolicy::no_retry(),
+            hedging: None,
+            breaker: None,
+        }
+    }
+
+    /// 再試行ポリシーを変更する。
+    ///
+    /// `ErrorKind::NotLeader`によるリーダの切り替えは、このポリシーとは独立に行われる。
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 再試行ポリシーを返す。
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// ヘッジングを有効にする。
+    ///
+    /// `ReadConsistency::Stale`ないし`ReadConsistency::Subset`で`get_object`と`head_object`を実行した際に、
+    /// `delay`が経過しても応答が得られない場合には、`peers`に含まれる別のノードに同じ要求を送信し、
+    /// 先に成功した方の結果を返す。
+    pub fn with_hedging(mut self, delay: Duration, peers: Vec<RemoteNodeId>) -> Self {
+        self.hedging = Some((delay, peers));
+        self
+    }
+
+    /// サーキットブレーカを設定する。
+    ///
+    /// 回路が開いているノードへの要求は、送信されずに`ErrorKind::Unavailable`で失敗する。
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    fn hedged_call<T, U>(
+        &self,
+        request: T::Req,
+        consistency: &ReadConsistency,
+        deadline: Deadline,
+    ) -> Hedged<Call<T, U>>
+    where
+        U: Send + 'static,
+        T: RpcCall<Res = Result<U>>,
+        T::Req: Clone + SetNodeId,
+        T::ReqEncoder: Default,
+        T::ResDecoder: Default,
+    {
+        let is_weak = matches!(
+            *consistency,
+            ReadConsistency::Stale | ReadConsistency::Subset(_)
+        );
+        let (delay, peers) = match self.hedging {
+            Some((delay, ref peers)) if is_weak => (delay, peers.clone()),
+            _ => return Hedged::new(Call::new(self, deadline, request)),
+        };
+        let primary = Call::new(self, deadline, request.clone());
+        let primary_node = primary.node.clone();
+        let client = self.clone();
+        Hedged::with_hedge(primary, delay, move || {
+            let node = peers.into_iter().find(|node| *node != primary_node)?;
+            Some(Call::with_node(&client, node, deadline, request))
+        })
+    }
+
+    /// キャッシュされているリーダノードを返す。
+    ///
+    /// リーダが未判明の場合には`None`が返される。
+    pub fn leader(&self) -> Option<RemoteNodeId> {
+        self.leader.lock().expect("Never fails").clone()
+    }
+
+    /// リーダノードのキャッシュを更新する。
+    pub fn set_leader(&self, leader: RemoteNodeId) {
+        *self.leader.lock().expect("Never fails") = Some(leader);
+    }
+
+    /// リーダノードのキャッシュを破棄する。
+    ///
+    /// 以降の要求は、再びリーダが判明するまで生成時に指定されたノードに送信される。
+    pub fn invalidate_leader(&self) {
+        *self.leader.lock().expect("Never fails") = None;
+    }
+
+    /// 要求の送信先となるノードを返す。
+    fn target(&self) -> RemoteNodeId {
+        self.leader().unwrap_or_else(|| self.node.clone())
     }
 
     /// `RecommendToLeaderRpc`を実行する。
@@ -37,75 +165,254 @@ impl Client {
     pub fn list_objects(
         &self,
         consistency: ReadConsistency,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<ObjectSummary>), Error = Error> {
         let request = mds::ListObjectsRequest {
             node_id: self.node.1.clone(),
             consistency,
         };
-        Call::<mds::ListObjectsRpc, _>::new(self, request)
+        Call::<mds::ListObjectsRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListObjectsPageRpc`を実行する。
+    pub fn list_objects_page(
+        &self,
+        consistency: ReadConsistency,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, ObjectSummaryPage), Error = Error> {
+        let request = mds::ListObjectsPageRequest {
+            node_id: self.node.1.clone(),
+            consistency,
+            start_after,
+            max_count,
+        };
+        Call::<mds::ListObjectsPageRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListObjectsPageRpc`を繰り返し実行して、オブジェクト一覧をページ単位で返す。
+    pub fn list_objects_pages(
+        &self,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Stream<Item = Vec<ObjectSummary>, Error = Error> {
+        let client = self.clone();
+        Pages::new(move |start_after| {
+            let request = mds::ListObjectsPageRequest {
+                node_id: client.node.1.clone(),
+                consistency: consistency.clone(),
+                start_after,
+                max_count,
+            };
+            Call::<mds::ListObjectsPageRpc, _>::new(&client, deadline, request)
+                .map(|(_, page)| page)
+        })
     }
 
     /// `ListObjectsByPrefixRpc`を実行する。
     pub fn list_objects_by_prefix(
         &self,
         prefix: ObjectPrefix,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<ObjectSummary>), Error = Error> {
         let request = mds::PrefixRequest {
             node_id: self.node.1.clone(),
             prefix,
         };
-        Call::<mds::ListObjectsByPrefixRpc, _>::new(self, request)
+        Call::<mds::ListObjectsByPrefixRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を実行する。
+    pub fn list_objects_by_prefix_page(
+        &self,
+        prefix: ObjectPrefix,
+        start_after: Option<ObjectId>,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, ObjectSummaryPage), Error = Error> {
+        let request = mds::PrefixPageRequest {
+            node_id: self.node.1.clone(),
+            prefix,
+            start_after,
+            max_count,
+        };
+        Call::<mds::ListObjectsByPrefixPageRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListObjectsByPrefixPageRpc`を繰り返し実行して、
+    /// 接頭辞に一致するオブジェクトの一覧をページ単位で返す。
+    pub fn list_objects_by_prefix_pages(
+        &self,
+        prefix: ObjectPrefix,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Stream<Item = Vec<ObjectSummary>, Error = Error> {
+        let client = self.clone();
+        Pages::new(move |start_after| {
+            let request = mds::PrefixPageRequest {
+                node_id: client.node.1.clone(),
+                prefix: prefix.clone(),
+                start_after,
+                max_count,
+            };
+            Call::<mds::ListObjectsByPrefixPageRpc, _>::new(&client, deadline, request)
+                .map(|(_, page)| page)
+        })
+    }
+
+    /// `WatchObjectsRpc`を実行する。
+    pub fn watch_objects(
+        &self,
+        since: ObjectVersion,
+        max_count: u32,
+        timeout: Duration,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, ObjectEvents), Error = Error> {
+        let request = mds::WatchObjectsRequest {
+            node_id: self.node.1.clone(),
+            since,
+            max_count,
+            timeout,
+        };
+        Call::<mds::WatchObjectsRpc, _>::new(self, deadline, request)
+    }
+
+    /// `WatchObjectsRpc`を繰り返し実行して、`since`より後に発生した変更通知を順に返す。
+    ///
+    /// ノードとの通信に失敗した場合(i.e., `ErrorKind::Unavailable`や`ErrorKind::Timeout`)には、
+    /// 最後に受信した変更の直後から取得を再開する。
+    ///
+    /// 終端のないストリームなのでデッドラインは指定しない。
+    /// 各要求のデッドラインは`timeout`に一定の猶予を加えたものとなる。
+    pub fn watch(
+        &self,
+        since: ObjectVersion,
+        max_count: u32,
+        timeout: Duration,
+    ) -> impl Stream<Item = ObjectEvent, Error = Error> {
+        Watch {
+            client: self.clone(),
+            since,
+            max_count,
+            timeout,
+            future: None,
+            retry_timer: None,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// `ListObjectsByAttributeRpc`を実行する。
+    pub fn list_objects_by_attribute(
+        &self,
+        filter: AttributeFilter,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<ObjectSummary>), Error = Error> {
+        let request = mds::AttributeRequest {
+            node_id: self.node.1.clone(),
+            filter,
+        };
+        Call::<mds::ListObjectsByAttributeRpc, _>::new(self, deadline, request)
     }
 
     /// `GetLatestVersionRpc`を実行する。
     pub fn latest_version(
         &self,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Option<ObjectSummary>), Error = Error> {
-        Call::<mds::GetLatestVersionRpc, _>::new(self, self.node.1.clone())
+        Call::<mds::GetLatestVersionRpc, _>::new(self, deadline, self.node.1.clone())
     }
 
     /// セグメントが保持しているオブジェクトの数を返す.
     pub fn object_count(
         &self,
         consistency: ReadConsistency,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, u64), Error = Error> {
         let request = mds::ObjectCountRequest {
             node_id: self.node.1.clone(),
             consistency,
         };
-        Call::<mds::GetObjectCountRpc, _>::new(self, request)
+        Call::<mds::GetObjectCountRpc, _>::new(self, deadline, request)
     }
 
     /// `GetObjectRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
     pub fn get_object(
         &self,
         id: ObjectId,
         expect: Expect,
         consistency: ReadConsistency,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Option<Metadata>), Error = Error> {
         let request = mds::ObjectRequest {
             node_id: self.node.1.clone(),
             object_id: id,
             expect,
-            consistency: Some(consistency),
+            consistency: Some(consistency.clone()),
         };
-        Call::<mds::GetObjectRpc, _>::new(self, request)
+        self.hedged_call::<mds::GetObjectRpc, _>(request, &consistency, deadline)
     }
 
     /// `HeadObjectRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
     pub fn head_object(
         &self,
         id: ObjectId,
         expect: Expect,
         consistency: ReadConsistency,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Option<ObjectVersion>), Error = Error> {
         let request = mds::ObjectRequest {
             node_id: self.node.1.clone(),
             object_id: id,
             expect,
-            consistency: Some(consistency),
+            consistency: Some(consistency.clone()),
+        };
+        self.hedged_call::<mds::HeadObjectRpc, _>(request, &consistency, deadline)
+    }
+
+    /// `GetObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
+    #[allow(clippy::type_complexity)]
+    pub fn get_object_with_info(
+        &self,
+        id: ObjectId,
+        expect: Expect,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, Option<(ObjectInfo, Vec<u8>)>), Error = Error>
+    {
+        let request = mds::ObjectRequest {
+            node_id: self.node.1.clone(),
+            object_id: id,
+            expect,
+            consistency: Some(consistency.clone()),
+        };
+        self.hedged_call::<mds::GetObjectInfoRpc, _>(request, &consistency, deadline)
+    }
+
+    /// `HeadObjectInfoRpc`を実行する。
+    ///
+    /// ヘッジングが有効な場合には、弱整合性の読み込みに限り、別のノードにも要求が送信される。
+    pub fn head_object_with_info(
+        &self,
+        id: ObjectId,
+        expect: Expect,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, Option<ObjectInfo>), Error = Error> {
+        let request = mds::ObjectRequest {
+            node_id: self.node.1.clone(),
+            object_id: id,
+            expect,
+            consistency: Some(consistency.clone()),
         };
-        Call::<mds::HeadObjectRpc, _>::new(self, request)
+        self.hedged_call::<mds::HeadObjectInfoRpc, _>(request, &consistency, deadline)
     }
 
     /// `PutObjectRpc`を実行する。
@@ -115,6 +422,7 @@ impl Client {
         metadata: Vec<u8>,
         expect: Expect,
         put_content_timeout: Duration,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, (ObjectVersion, Option<ObjectVersion>)), Error = Error>
     {
         let request = mds::PutObjectRequest {
@@ -124,7 +432,45 @@ impl Client {
             expect,
             put_content_timeout,
         };
-        Call::<mds::PutObjectRpc, _>::new(self, request)
+        Call::<mds::PutObjectRpc, _>::new(self, deadline, request)
+    }
+
+    /// `PutObjectWithOptionsRpc`を実行する。
+    pub fn put_object_with_options(
+        &self,
+        id: ObjectId,
+        metadata: Vec<u8>,
+        expect: Expect,
+        put_content_timeout: Duration,
+        deadline: Deadline,
+        options: PutObjectOptions,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, (ObjectVersion, Option<ObjectVersion>)), Error = Error>
+    {
+        let request = mds::PutObjectWithOptionsRequest {
+            node_id: self.node.1.clone(),
+            object_id: id,
+            metadata,
+            expect,
+            put_content_timeout,
+            expiry: options.expiry,
+            attributes: options.attributes,
+        };
+        Call::<mds::PutObjectWithOptionsRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListExpiringObjectsRpc`を実行する。
+    pub fn list_expiring_objects(
+        &self,
+        before: UnixTimestamp,
+        consistency: ReadConsistency,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<ObjectSummary>), Error = Error> {
+        let request = mds::ExpiringObjectsRequest {
+            node_id: self.node.1.clone(),
+            consistency,
+            before,
+        };
+        Call::<mds::ListExpiringObjectsRpc, _>::new(self, deadline, request)
     }
 
     /// `DeleteObjectRpc`を実行する。
@@ -132,6 +478,7 @@ impl Client {
         &self,
         id: ObjectId,
         expect: Expect,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Option<ObjectVersion>), Error = Error> {
         let request = mds::ObjectRequest {
             node_id: self.node.1.clone(),
@@ -139,47 +486,145 @@ impl Client {
             expect,
             consistency: None,
         };
-        Call::<mds::DeleteObjectRpc, _>::new(self, request)
+        Call::<mds::DeleteObjectRpc, _>::new(self, deadline, request)
+    }
+
+    /// `TransactionRpc`を実行する。
+    ///
+    /// 結果は`operations`と同じ順序で返される。
+    pub fn transaction(
+        &self,
+        operations: Vec<TransactionOperation>,
+        put_content_timeout: Duration,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<TransactionOutcome>), Error = Error> {
+        let request = mds::TransactionRequest {
+            node_id: self.node.1.clone(),
+            operations,
+            put_content_timeout,
+        };
+        Call::<mds::TransactionRpc, _>::new(self, deadline, request)
     }
 
     /// `DeleteObjectByVersionRpc`を実行する。
     pub fn delete_object_by_version(
         &self,
         version: ObjectVersion,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Option<ObjectVersion>), Error = Error> {
         let request = mds::VersionRequest {
             node_id: self.node.1.clone(),
             object_version: version,
         };
-        Call::<mds::DeleteObjectByVersionRpc, _>::new(self, request)
+        Call::<mds::DeleteObjectByVersionRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListObjectsByRangeRpc`を実行する。
+    pub fn list_objects_by_range(
+        &self,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<ObjectSummary>), Error = Error> {
+        let request = mds::RangePageRequest {
+            node_id: self.node.1.clone(),
+            targets,
+            consistency,
+            max_count,
+        };
+        Call::<mds::ListObjectsByRangeRpc, _>::new(self, deadline, request)
+    }
+
+    /// `ListObjectsByRangeRpc`を繰り返し実行して、
+    /// 指定範囲のバージョンを持つオブジェクトの一覧を、バージョンの昇順にページ単位で返す。
+    pub fn list_objects_by_range_pages(
+        &self,
+        targets: Range<ObjectVersion>,
+        consistency: ReadConsistency,
+        max_count: u32,
+        deadline: Deadline,
+    ) -> impl Stream<Item = Vec<ObjectSummary>, Error = Error> {
+        let client = self.clone();
+        let Range { start, end } = targets;
+        Pages::new(move |next| {
+            let request = mds::RangePageRequest {
+                node_id: client.node.1.clone(),
+                targets: next.unwrap_or(start)..end,
+                consistency: consistency.clone(),
+                max_count,
+            };
+            Call::<mds::ListObjectsByRangeRpc, _>::new(&client, deadline, request)
+                .map(move |(_, summaries)| range_page(summaries, max_count, end))
+        })
+        .filter(|summaries| !summaries.is_empty())
     }
 
     /// `DeleteObjectsByRangeRpc`を実行する。
     pub fn delete_by_range(
         &self,
         targets: Range<ObjectVersion>,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<ObjectSummary>), Error = Error> {
         let request = mds::RangeRequest {
             node_id: self.node.1.clone(),
             targets,
         };
-        Call::<mds::DeleteObjectsByRangeRpc, _>::new(self, request)
+        Call::<mds::DeleteObjectsByRangeRpc, _>::new(self, deadline, request)
     }
 
     /// `DeleteObjectsByPrefixRpc`を実行する。
     pub fn delete_by_prefix(
         &self,
         prefix: ObjectPrefix,
+        deadline: Deadline,
     ) -> impl Future<Item = (Option<RemoteNodeId>, DeleteObjectsByPrefixSummary), Error = Error>
     {
         let request = mds::PrefixRequest {
             node_id: self.node.1.clone(),
             prefix,
         };
-        Call::<mds::DeleteObjectsByPrefixRpc, _>::new(self, request)
+        Call::<mds::DeleteObjectsByPrefixRpc, _>::new(self, deadline, request)
+    }
+
+    /// `PreviewDeleteObjectsByRangeRpc`を実行する。
+    pub fn preview_delete_by_range(
+        &self,
+        targets: Range<ObjectVersion>,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, Vec<ObjectSummary>), Error = Error> {
+        let request = mds::RangeRequest {
+            node_id: self.node.1.clone(),
+            targets,
+        };
+        Call::<mds::PreviewDeleteObjectsByRangeRpc, _>::new(self, deadline, request)
+    }
+
+    /// `PreviewDeleteObjectsByPrefixRpc`を実行する。
+    pub fn preview_delete_by_prefix(
+        &self,
+        prefix: ObjectPrefix,
+        deadline: Deadline,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, DeleteObjectsByPrefixSummary), Error = Error>
+    {
+        let request = mds::PrefixRequest {
+            node_id: self.node.1.clone(),
+            prefix,
+        };
+        Call::<mds::PreviewDeleteObjectsByPrefixRpc, _>::new(self, deadline, request)
     }
 }
 
+/// オブジェクト保存時に指定可能なオプション。
+#[derive(Debug, Clone, Default)]
+pub struct PutObjectOptions {
+    /// オブジェクトの有効期限。
+    pub expiry: Option<Expiry>,
+
+    /// ユーザ定義の属性群。
+    pub attributes: Attributes,
+}
+
 trait SetNodeId {
     fn set_node_id(&mut self, node_id: LocalNodeId);
 }
@@ -188,50 +633,51 @@ impl SetNodeId for LocalNodeId {
         *self = node_id;
     }
 }
-impl SetNodeId for mds::ListObjectsRequest {
-    fn set_node_id(&mut self, node_id: LocalNodeId) {
-        self.node_id = node_id;
-    }
-}
-impl SetNodeId for mds::ObjectRequest {
-    fn set_node_id(&mut self, node_id: LocalNodeId) {
-        self.node_id = node_id;
-    }
-}
-impl SetNodeId for mds::ObjectCountRequest {
-    fn set_node_id(&mut self, node_id: LocalNodeId) {
-        self.node_id = node_id;
-    }
-}
-impl SetNodeId for mds::VersionRequest {
-    fn set_node_id(&mut self, node_id: LocalNodeId) {
-        self.node_id = node_id;
-    }
-}
-impl SetNodeId for mds::RangeRequest {
-    fn set_node_id(&mut self, node_id: LocalNodeId) {
-        self.node_id = node_id;
-    }
-}
-impl SetNodeId for mds::PrefixRequest {
-    fn set_node_id(&mut self, node_id: LocalNodeId) {
-        self.node_id = node_id;
-    }
-}
-impl SetNodeId for mds::PutObjectRequest {
-    fn set_node_id(&mut self, node_id: LocalNodeId) {
-        self.node_id = node_id;
-    }
+/// `node_id`フィールドを持つ要求に`SetNodeId`を実装する。
+macro_rules! impl_set_node_id {
+    ($($request:ty),*) => {
+        $(
+            impl SetNodeId for $request {
+                fn set_node_id(&mut self, node_id: LocalNodeId) {
+                    self.node_id = node_id;
+                }
+            }
+        )*
+    };
 }
+impl_set_node_id!(
+    mds::ListObjectsRequest,
+    mds::ListObjectsPageRequest,
+    mds::ObjectRequest,
+    mds::ObjectCountRequest,
+    mds::VersionRequest,
+    mds::RangeRequest,
+    mds::RangePageRequest,
+    mds::PrefixRequest,
+    mds::PrefixPageRequest,
+    mds::PutObjectRequest,
+    mds::PutObjectWithOptionsRequest,
+    mds::WatchObjectsRequest,
+    mds::ExpiringObjectsRequest,
+    mds::AttributeRequest,
+    mds::TransactionRequest
+);
 
 #[derive(Debug)]
 struct Call<T: RpcCall, U> {
+    contact_node: RemoteNodeId,
     node: RemoteNodeId,
     rpc_service: RpcServiceHandle,
-    leader: Option<Response<RemoteNodeId>>,
+    leader_cache: Arc<Mutex<Option<RemoteNodeId>>>,
+    leader: Option<Guarded<Response<RemoteNodeId>>>,
+    is_leader_changed: bool,
     request: T::Req,
-    response: Option<Response<U>>,
-    retried_count: usize,
+    response: Option<Guarded<Response<U>>>,
+    breaker: Option<CircuitBreaker>,
+    deadline: Deadline,
+    retry: Retry,
+    needs_leader_lookup: bool,
+    redirects: usize,
 }
 impl<T: RpcCall, U> Call<T, U>
 where
@@ -241,15 +687,54 @@ where
     T::ReqEncoder: Default,
     T::ResDecoder: Default,
 {
-    fn new(client: &Client, request: T::Req) -> Self {
-        let future = T::client(&client.rpc_service).call(client.node.0, request.clone());
-        Call {
-            node: client.node.clone(),
+    fn new(client: &Client, deadline: Deadline, request: T::Req) -> Self {
+        Self::with_node(client, client.target(), deadline, request)
+    }
+
+    fn with_node(client: &Client, node: RemoteNodeId, deadline: Deadline, request: T::Req) -> Self {
+        let mut call = Call {
+            contact_node: client.node.clone(),
+            node,
             rpc_service: client.rpc_service.clone(),
+            leader_cache: Arc::clone(&client.leader),
             leader: None,
+            is_leader_changed: false,
             request,
-            response: Some(Response(future)),
-            retried_count: 0,
+            response: None,
+            breaker: client.breaker.clone(),
+            deadline,
+            retry: Retry::new(client.retry_policy.clone(), deadline),
+            needs_leader_lookup: false,
+            redirects: 0,
+        };
+        call.send();
+        call
+    }
+
+    fn send(&mut self) {
+        self.request.set_node_id(self.node.1.clone());
+        let rpc_service = &self.rpc_service;
+        let server = self.node.0;
+        let request = &self.request;
+        let future = Guarded::new(self.breaker.as_ref(), server, self.deadline, |timeout| {
+            Response::call::<T>(rpc_service, server, request.clone(), timeout)
+        });
+        self.response = Some(future);
+    }
+
+    fn restart(&mut self) {
+        if self.needs_leader_lookup {
+            self.needs_leader_lookup = false;
+            let rpc_service = &self.rpc_service;
+            let node = &self.node;
+            let future = Guarded::new(None, node.0, self.deadline, |timeout| {
+                Response::call::<mds::GetLeaderRpc>(rpc_service, node.0, node.1.clone(), timeout)
+            });
+            self.leader = Some(future);
+        } else {
+            let leader = self.leader_cache.lock().expect("Never fails").clone();
+            self.node = leader.unwrap_or_else(|| self.contact_node.clone());
+            self.send();
         }
     }
 }
@@ -266,38 +751,67 @@ where
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
         loop {
+            match track!(self.retry.poll())? {
+                Async::NotReady => break,
+                Async::Ready(true) => self.restart(),
+                Async::Ready(false) => {}
+            }
+
             match self.response.poll() {
                 Err(e) => {
-                    if *e.kind() == ErrorKind::NotLeader {
+                    let kind = *e.kind();
+                    if kind == ErrorKind::Unavailable {
+                        // NOTE: キャッシュされていたリーダが停止している可能性がある
+                        let mut cache = self.leader_cache.lock().expect("Never fails");
+                        if cache.as_ref() == Some(&self.node) {
+                            *cache = None;
+                        }
+                    }
+                    self.response = None;
+                    track_assert!(!self.deadline.is_expired(), ErrorKind::Timeout; e, T::NAME);
+                    if kind == ErrorKind::NotLeader {
+                        // NOTE: リーダの切り替えへの追従は、再試行ポリシーに依らず常に行う
                         track_assert!(
-                            self.retried_count < 2,
+                            self.redirects < MAX_LEADER_REDIRECTS,
                             ErrorKind::Unavailable,
                             "Unstable cluster: RPC={}",
                             T::NAME
                         );
-
-                        self.retried_count += 1;
-                        let future = mds::GetLeaderRpc::client(&self.rpc_service)
-                            .call(self.node.0, self.node.1.clone());
-                        self.leader = Some(Response(future));
-                        self.response = None;
-                    } else {
+                        self.redirects += 1;
+                        self.needs_leader_lookup = true;
+                        self.restart();
+                        continue;
+                    }
+                    if let Err(e) = self.retry.schedule(e) {
                         return Err(track!(e, T::NAME));
                     }
+                    continue;
                 }
                 Ok(Async::NotReady) => break,
                 Ok(Async::Ready(None)) => {}
                 Ok(Async::Ready(Some(response))) => {
-                    let new_leader = self.leader.as_ref().map(|_| self.node.clone());
+                    let new_leader = if self.is_leader_changed {
+                        Some(self.node.clone())
+                    } else {
+                        None
+                    };
                     return Ok(Async::Ready((new_leader, response)));
                 }
             }
 
-            if let Async::Ready(Some(leader)) = track!(self.leader.poll())? {
+            let polled = match self.leader.poll() {
+                Err(e) => {
+                    track_assert!(!self.deadline.is_expired(), ErrorKind::Timeout; e, T::NAME);
+                    return Err(track!(e, T::NAME));
+                }
+                Ok(polled) => polled,
+            };
+            if let Async::Ready(Some(leader)) = polled {
+                *self.leader_cache.lock().expect("Never fails") = Some(leader.clone());
+                self.leader = None;
+                self.is_leader_changed = true;
                 self.node = leader;
-                self.request.set_node_id(self.node.1.clone());
-                let future = T::client(&self.rpc_service).call(self.node.0, self.request.clone());
-                self.response = Some(Response(future));
+                self.send();
             } else {
                 break;
             }
@@ -305,3 +819,60 @@ where
         Ok(Async::NotReady)
     }
 }
+
+#[derive(Debug)]
+struct Watch {
+    client: Client,
+    since: ObjectVersion,
+    max_count: u32,
+    timeout: Duration,
+    future: Option<Call<mds::WatchObjectsRpc, ObjectEvents>>,
+    retry_timer: Option<Timeout>,
+    events: VecDeque<ObjectEvent>,
+}
+impl Stream for Watch {
+    type Item = ObjectEvent;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(Async::Ready(Some(event)));
+            }
+            if let Some(mut timer) = self.retry_timer.take() {
+                if let Ok(Async::NotReady) = timer.poll() {
+                    self.retry_timer = Some(timer);
+                    return Ok(Async::NotReady);
+                }
+            }
+            if self.future.is_none() {
+                let request = mds::WatchObjectsRequest {
+                    node_id: self.client.node.1.clone(),
+                    since: self.since,
+                    max_count: self.max_count,
+                    timeout: self.timeout,
+                };
+                let deadline = Deadline::new(self.timeout + WATCH_DEADLINE_MARGIN);
+                self.future = Some(Call::new(&self.client, deadline, request));
+            }
+
+            match self.future.poll() {
+                Err(e) => {
+                    self.future = None;
+                    match *e.kind() {
+                        ErrorKind::Unavailable | ErrorKind::Timeout => {
+                            self.retry_timer = Some(timer::timeout(WATCH_RETRY_INTERVAL));
+                        }
+                        _ => return Err(track!(e)),
+                    }
+                }
+                Ok(Async::Ready(Some((_, events)))) => {
+                    self.future = None;
+                    self.since = events.next;
+                    self.events.extend(events.events);
+                }
+                Ok(_) => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
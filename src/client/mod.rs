@@ -1,16 +1,60 @@
 //! RPCクライアント。
+use fibers::fiber::{self, Unpark};
+use fibers::time::timer::{self, Timeout};
+use fibers::Spawn;
 use fibers_rpc;
-use futures::{Async, Future, Poll};
+use fibers_rpc::client::ClientServiceHandle as RpcServiceHandle;
+use fibers_rpc::Call as RpcCall;
+use futures::executor::{self, Notify, NotifyHandle};
+use futures::sync::{mpsc, oneshot};
+use futures::{Async, AsyncSink, Future, Poll, Sink, Stream};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use trackable::error::ErrorKindExt;
 
+use deadline::Deadline;
+use entity::object::{ObjectId, ObjectSummary, ObjectSummaryPage, ObjectVersion};
 use {Error, ErrorKind, Result};
 
+pub mod blocking;
+pub mod breaker;
+pub mod cluster;
+#[cfg(feature = "compat")]
+pub mod compat;
 pub mod config;
 pub mod frugalos;
 pub mod mds;
+pub mod pool;
+pub mod retry;
+
+use self::breaker::CircuitBreaker;
+use self::retry::RetryPolicy;
 
 #[derive(Debug)]
 struct Response<T>(fibers_rpc::client::Response<Result<T>>);
+impl<T> Response<T>
+where
+    T: Send + 'static,
+{
+    /// `timeout`をタイムアウトとして、`server`に要求を送信する。
+    fn call<C>(
+        rpc_service: &RpcServiceHandle,
+        server: SocketAddr,
+        request: C::Req,
+        timeout: Duration,
+    ) -> Self
+    where
+        C: RpcCall<Res = Result<T>>,
+        C::ReqEncoder: Default,
+        C::ResDecoder: Default,
+    {
+        let mut client = C::client(rpc_service);
+        client.options_mut().timeout = Some(timeout);
+        Response(client.call(server, request))
+    }
+}
 impl<T> Future for Response<T> {
     type Item = T;
     type Error = Error;
@@ -31,3 +75,622 @@ impl<T> Future for Response<T> {
         }
     }
 }
+
+/// デッドラインとサーキットブレーカを確認した上で要求を送信する`Future`。
+///
+/// サーキットブレーカが指定された場合には、要求の結果が記録される。
+/// ただし、呼び出し側のデッドラインに達したことによるタイムアウトは、サーバの障害とはみなさない。
+#[derive(Debug)]
+struct Guarded<F> {
+    breaker: Option<CircuitBreaker>,
+    server: SocketAddr,
+    deadline: Deadline,
+    future: Option<F>,
+    rejected: Option<Error>,
+}
+impl<F> Guarded<F>
+where
+    F: Future<Error = Error>,
+{
+    /// デッドラインを過ぎておらず、かつ回路が閉じている場合にのみ、
+    /// デッドラインまでの残り時間を引数として`send`を呼び出す。
+    ///
+    /// デッドラインを過ぎている場合には`ErrorKind::Timeout`で失敗する。
+    fn new<G>(
+        breaker: Option<&CircuitBreaker>,
+        server: SocketAddr,
+        deadline: Deadline,
+        send: G,
+    ) -> Self
+    where
+        G: FnOnce(Duration) -> F,
+    {
+        let timeout = deadline.remaining();
+        let rejected = if timeout == Duration::from_secs(0) {
+            let e: Error = ErrorKind::Timeout
+                .cause(format!("Deadline exceeded: server={}", server))
+                .into();
+            Some(track!(e))
+        } else {
+            breaker.and_then(|b| track!(b.try_acquire(server)).err())
+        };
+        let future = if rejected.is_none() {
+            Some(send(timeout))
+        } else {
+            None
+        };
+        Guarded {
+            breaker: breaker.cloned(),
+            server,
+            deadline,
+            future,
+            rejected,
+        }
+    }
+}
+impl<F> Future for Guarded<F>
+where
+    F: Future<Error = Error>,
+{
+    type Item = F::Item;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(e) = self.rejected.take() {
+            return Err(e);
+        }
+        let result = self.future.poll();
+        if let Some(ref breaker) = self.breaker {
+            match result {
+                Err(ref e) => match *e.kind() {
+                    ErrorKind::Timeout if self.deadline.is_expired() => {
+                        // NOTE: 呼び出し側が短いデッドラインを指定した場合にも、健全なサーバの回路を開かないようにする
+                        breaker.release(self.server)
+                    }
+                    ErrorKind::Unavailable | ErrorKind::Timeout => {
+                        breaker.record_failure(self.server)
+                    }
+                    _ => breaker.record_success(self.server),
+                },
+                Ok(Async::Ready(_)) => breaker.record_success(self.server),
+                Ok(Async::NotReady) => {}
+            }
+        }
+        match track!(result)? {
+            Async::Ready(Some(item)) => Ok(Async::Ready(item)),
+            _ => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// `RetryPolicy`に従って、RPCの再試行の可否とタイミングを管理する。
+#[derive(Debug)]
+struct Retry {
+    policy: RetryPolicy,
+    attempts: usize,
+    deadline: Deadline,
+    scheduled: bool,
+    timer: Option<Timeout>,
+}
+impl Retry {
+    fn new(policy: RetryPolicy, deadline: Deadline) -> Self {
+        Retry {
+            policy,
+            attempts: 1,
+            deadline,
+            scheduled: false,
+            timer: None,
+        }
+    }
+
+    fn may_retry(&self) -> bool {
+        self.attempts < self.policy.max_attempts
+    }
+
+    /// `error`を受けて再試行を予約する。
+    ///
+    /// 再試行できない場合(e.g., 対象外のエラー、試行回数の超過、デッドライン超過)には`error`がそのまま返される。
+    fn schedule(&mut self, error: Error) -> Result<()> {
+        if !self.may_retry() || !self.policy.is_retryable(error.kind()) {
+            return Err(error);
+        }
+        let backoff = self.policy.backoff(self.attempts);
+        if backoff >= self.deadline.remaining() {
+            return Err(error);
+        }
+        self.attempts += 1;
+        self.scheduled = true;
+        if backoff != Duration::from_secs(0) {
+            self.timer = Some(timer::timeout(backoff));
+        }
+        Ok(())
+    }
+
+    /// 予約された再試行の実行時刻に達した場合には`true`を返す。
+    ///
+    /// 再試行が予約されていない場合には`false`が返される。
+    fn poll(&mut self) -> Poll<bool, Error> {
+        if let Some(mut timer) = self.timer.take() {
+            match timer.poll() {
+                Err(e) => return Err(track!(Error::from(ErrorKind::Other.cause(e)))),
+                Ok(Async::NotReady) => {
+                    self.timer = Some(timer);
+                    return Ok(Async::NotReady);
+                }
+                Ok(Async::Ready(())) => {}
+            }
+        }
+        let scheduled = self.scheduled;
+        self.scheduled = false;
+        Ok(Async::Ready(scheduled))
+    }
+}
+
+/// 二番目の要求を送信する関数。
+type HedgeFn<F> = Box<dyn FnOnce() -> Option<F> + Send>;
+
+/// 一定時間内に応答が得られない場合に、別のサーバに同じ要求を送信する`Future`。
+///
+/// 先に成功した方の結果が返され、他方の要求は破棄される。
+/// 最初の要求が通信エラー(`ErrorKind::Unavailable`)やタイムアウトで失敗した場合には、
+/// 待ち時間の経過を待たずに二番目の要求が送信される。
+/// それ以外のエラー(e.g., `Expect`の不一致)は、別のサーバでも同じ結果となるため、そのまま返される。
+///
+/// 両方の要求が失敗した場合には、最初の要求のエラーが返される。
+struct Hedged<F> {
+    primary: Option<F>,
+    secondary: Option<F>,
+    hedge: Option<(Timeout, HedgeFn<F>)>,
+    last_error: Option<Error>,
+}
+impl<F> Hedged<F>
+where
+    F: Future<Error = Error>,
+{
+    fn new(primary: F) -> Self {
+        Hedged {
+            primary: Some(primary),
+            secondary: None,
+            hedge: None,
+            last_error: None,
+        }
+    }
+
+    /// `delay`経過後に`hedge`を呼び出して、二番目の要求を送信する。
+    ///
+    /// `hedge`が`None`を返した場合(e.g., 他に送信先が存在しない)には、最初の要求の結果のみを待つ。
+    fn with_hedge<G>(primary: F, delay: Duration, hedge: G) -> Self
+    where
+        G: FnOnce() -> Option<F> + Send + 'static,
+    {
+        Hedged {
+            primary: Some(primary),
+            secondary: None,
+            hedge: Some((timer::timeout(delay), Box::new(hedge))),
+            last_error: None,
+        }
+    }
+
+    fn start_secondary(&mut self) {
+        if let Some((_, hedge)) = self.hedge.take() {
+            self.secondary = hedge();
+        }
+    }
+}
+impl<F> Future for Hedged<F>
+where
+    F: Future<Error = Error>,
+{
+    type Item = F::Item;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let is_expired = match self.hedge {
+            None => false,
+            Some((ref mut timer, _)) => !matches!(timer.poll(), Ok(Async::NotReady)),
+        };
+        if is_expired {
+            self.start_secondary();
+        }
+
+        match self.primary.poll() {
+            Err(e) => {
+                self.primary = None;
+                match *e.kind() {
+                    ErrorKind::Unavailable | ErrorKind::Timeout => {
+                        self.last_error = Some(e);
+                        self.start_secondary();
+                    }
+                    _ => {
+                        self.secondary = None;
+                        self.hedge = None;
+                        return Err(track!(e));
+                    }
+                }
+            }
+            Ok(Async::Ready(Some(item))) => {
+                self.secondary = None;
+                return Ok(Async::Ready(item));
+            }
+            Ok(_) => {}
+        }
+        match self.secondary.poll() {
+            Err(e) => {
+                self.secondary = None;
+                if self.last_error.is_none() {
+                    self.last_error = Some(e);
+                }
+            }
+            Ok(Async::Ready(Some(item))) => {
+                self.primary = None;
+                return Ok(Async::Ready(item));
+            }
+            Ok(_) => {}
+        }
+
+        if self.primary.is_none() && self.secondary.is_none() {
+            let e = self.last_error.take().expect("Never fails");
+            return Err(track!(e));
+        }
+        Ok(Async::NotReady)
+    }
+}
+impl<F: fmt::Debug> fmt::Debug for Hedged<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hedged")
+            .field("primary", &self.primary)
+            .field("secondary", &self.secondary)
+            .field("is_hedge_pending", &self.hedge.is_some())
+            .field("last_error", &self.last_error)
+            .finish()
+    }
+}
+
+/// `Pages`が扱うページ。
+trait Page {
+    /// 次のページの取得開始位置を表す型。
+    type Cursor: fmt::Debug;
+
+    /// ページを、要約一覧と次のページの取得開始位置に分解する。
+    ///
+    /// 次のページが存在しない場合には、取得開始位置は`None`となる。
+    fn into_parts(self) -> (Vec<ObjectSummary>, Option<Self::Cursor>);
+}
+impl Page for ObjectSummaryPage {
+    type Cursor = ObjectId;
+
+    fn into_parts(self) -> (Vec<ObjectSummary>, Option<Self::Cursor>) {
+        (self.summaries, self.next)
+    }
+}
+impl<C: fmt::Debug> Page for (Vec<ObjectSummary>, Option<C>) {
+    type Cursor = C;
+
+    fn into_parts(self) -> (Vec<ObjectSummary>, Option<Self::Cursor>) {
+        self
+    }
+}
+
+/// バージョンの範囲指定での一覧取得結果を、`Pages`が扱うページに変換する。
+///
+/// 結果の要素数が`max_count`に満たない場合や、範囲の終端に達した場合には、次のページは存在しない。
+fn range_page(
+    summaries: Vec<ObjectSummary>,
+    max_count: u32,
+    end: ObjectVersion,
+) -> (Vec<ObjectSummary>, Option<ObjectVersion>) {
+    let next = if summaries.len() < max_count as usize {
+        None
+    } else {
+        summaries
+            .last()
+            .map(|s| ObjectVersion(s.version.0 + 1))
+            .filter(|&next| next < end)
+    };
+    (summaries, next)
+}
+
+/// ページ単位の一覧取得を繰り返し実行して、各ページの要約一覧を返す`Stream`。
+///
+/// `fetch`には、前のページが返した取得開始位置が渡される（最初のページでは`None`）。
+#[derive(Debug)]
+struct Pages<F, T>
+where
+    T: Future,
+    T::Item: Page,
+{
+    fetch: F,
+    future: Option<T>,
+    cursor: Option<<T::Item as Page>::Cursor>,
+    eos: bool,
+}
+impl<F, T> Pages<F, T>
+where
+    F: FnMut(Option<<T::Item as Page>::Cursor>) -> T,
+    T: Future<Error = Error>,
+    T::Item: Page,
+{
+    fn new(fetch: F) -> Self {
+        Pages {
+            fetch,
+            future: None,
+            cursor: None,
+            eos: false,
+        }
+    }
+}
+impl<F, T> Stream for Pages<F, T>
+where
+    F: FnMut(Option<<T::Item as Page>::Cursor>) -> T,
+    T: Future<Error = Error>,
+    T::Item: Page,
+{
+    type Item = Vec<ObjectSummary>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if self.eos {
+            return Ok(Async::Ready(None));
+        }
+        if self.future.is_none() {
+            let future = (self.fetch)(self.cursor.take());
+            self.future = Some(future);
+        }
+        match track!(self.future.poll())? {
+            Async::Ready(Some(page)) => {
+                self.future = None;
+                let (summaries, next) = page.into_parts();
+                match next {
+                    Some(next) => self.cursor = Some(next),
+                    None => self.eos = true,
+                }
+                Ok(Async::Ready(Some(summaries)))
+            }
+            _ => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// `future`を`spawner`上で実行し、その結果を受け取るためのチャンネルを返す。
+///
+/// 返されたチャンネルが破棄された場合には、`future`の実行も中断される。
+fn spawn_future<S, F>(spawner: &S, future: F) -> oneshot::Receiver<Result<F::Item>>
+where
+    S: Spawn,
+    F: Future<Error = Error> + Send + 'static,
+    F::Item: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    spawner.spawn(FiberTask::new(ForwardFuture {
+        future,
+        tx: Some(tx),
+    }));
+    rx
+}
+
+/// `stream`を`spawner`上で実行し、その要素を受け取るためのチャンネルを返す。
+///
+/// 各要素は`Ok(Some(_))`として送信され、`stream`が終端した場合には終端を示す`Ok(None)`が送信される。
+/// `stream`がエラーを返した場合には、そのエラーを最後の要素としてチャンネルを閉じる。
+/// そのため、受信側はこれらを受け取る前にチャンネルが閉じた場合には、ファイバーが異常終了したものとして扱うこと。
+///
+/// 返されたチャンネルが破棄された場合には、`stream`の実行も中断される。
+fn spawn_stream<S, T>(spawner: &S, stream: T) -> mpsc::Receiver<Result<Option<T::Item>>>
+where
+    S: Spawn,
+    T: Stream<Error = Error> + Send + 'static,
+    T::Item: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(0);
+    spawner.spawn(FiberTask::new(ForwardStream {
+        stream,
+        tx,
+        pending: None,
+        is_finished: false,
+    }));
+    rx
+}
+
+/// `spawn_future`や`spawn_stream`で起動したファイバーが、結果を返す前に停止した場合のエラーを返す。
+fn terminated() -> Error {
+    ErrorKind::Other
+        .cause("Background fiber was terminated unexpectedly")
+        .into()
+}
+
+/// `futures`のタスクの通知を、ファイバーの再開に変換する。
+#[derive(Default)]
+struct FiberNotify {
+    unpark: Mutex<Option<Unpark>>,
+}
+impl Notify for FiberNotify {
+    fn notify(&self, _id: usize) {
+        // NOTE: `Unpark`は破棄時にファイバーを再開させる
+        self.unpark.lock().expect("Never fails").take();
+    }
+}
+
+/// `futures`のタスクのコンテキスト内で、Futureをポーリングする。
+///
+/// `fibers`のファイバーは`futures`のタスクのコンテキストを提供しないため、
+/// `futures::sync`のチャンネルを扱うFutureは、このラッパーを介して実行する必要がある。
+struct FiberTask<F> {
+    future: executor::Spawn<F>,
+    notify: Arc<FiberNotify>,
+}
+impl<F: Future> FiberTask<F> {
+    fn new(future: F) -> Self {
+        FiberTask {
+            future: executor::spawn(future),
+            notify: Arc::default(),
+        }
+    }
+}
+impl<F: Future> Future for FiberTask<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        {
+            // NOTE: 通知の取りこぼしを防ぐために、ポーリングの前にファイバーを待機状態にしておく
+            let mut unpark = self.notify.unpark.lock().expect("Never fails");
+            if unpark.is_none() {
+                *unpark = fiber::with_current_context(|mut c| c.park());
+            }
+        }
+        let notify = NotifyHandle::from(Arc::clone(&self.notify));
+        self.future.poll_future_notify(&notify, 0)
+    }
+}
+
+/// Futureの結果を`tx`に送信する。
+struct ForwardFuture<F: Future> {
+    future: F,
+    tx: Option<oneshot::Sender<Result<F::Item>>>,
+}
+impl<F> Future for ForwardFuture<F>
+where
+    F: Future<Error = Error>,
+{
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let result = match self.future.poll() {
+            Ok(Async::NotReady) => {
+                let tx = self.tx.as_mut().expect("Cannot poll ForwardFuture twice");
+                return tx.poll_cancel();
+            }
+            Ok(Async::Ready(item)) => Ok(item),
+            Err(e) => Err(e),
+        };
+        let tx = self.tx.take().expect("Cannot poll ForwardFuture twice");
+        let _ = tx.send(result);
+        Ok(Async::Ready(()))
+    }
+}
+
+/// Streamの要素を`tx`に送信する。
+struct ForwardStream<S: Stream> {
+    stream: S,
+    tx: mpsc::Sender<Result<Option<S::Item>>>,
+    pending: Option<Result<Option<S::Item>>>,
+    is_finished: bool,
+}
+impl<S> Future for ForwardStream<S>
+where
+    S: Stream<Error = Error>,
+{
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            if let Some(item) = self.pending.take() {
+                match self.tx.start_send(item) {
+                    Err(_) => return Ok(Async::Ready(())),
+                    Ok(AsyncSink::NotReady(item)) => {
+                        self.pending = Some(item);
+                        return Ok(Async::NotReady);
+                    }
+                    Ok(AsyncSink::Ready) => {}
+                }
+            }
+            if self.is_finished {
+                return self.tx.close().map_err(|_| ());
+            }
+
+            // NOTE: 受信側が破棄されている場合には、元のStreamのポーリングを止める
+            if self.tx.poll_ready().is_err() {
+                return Ok(Async::Ready(()));
+            }
+            match self.stream.poll() {
+                Err(e) => {
+                    self.pending = Some(Err(e));
+                    self.is_finished = true;
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(None)) => {
+                    self.pending = Some(Ok(None));
+                    self.is_finished = true;
+                }
+                Ok(Async::Ready(Some(item))) => self.pending = Some(Ok(Some(item))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    use super::breaker::{CircuitBreakerConfig, CircuitState};
+    use super::*;
+
+    type BoxFuture = Box<dyn Future<Item = u32, Error = Error> + Send>;
+
+    fn failed(kind: ErrorKind) -> BoxFuture {
+        Box::new(future::err(kind.error().into()))
+    }
+
+    fn hedged(primary: BoxFuture, secondary: BoxFuture) -> (Hedged<BoxFuture>, Arc<AtomicBool>) {
+        let is_hedged = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&is_hedged);
+        let future = Hedged::with_hedge(primary, Duration::from_secs(3600), move || {
+            flag.store(true, Ordering::SeqCst);
+            Some(secondary)
+        });
+        (future, is_hedged)
+    }
+
+    fn guarded_timeout(breaker: &CircuitBreaker, deadline: Deadline) -> Result<u32> {
+        let server = "127.0.0.1:1000".parse().unwrap();
+        let future = Guarded::new(Some(breaker), server, deadline, |_| {
+            future::err::<u32, Error>(ErrorKind::Timeout.error().into())
+        });
+        thread::sleep(Duration::from_millis(20));
+        future.wait()
+    }
+
+    #[test]
+    fn guarded_ignores_timeouts_caused_by_the_deadline() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        });
+        let server = "127.0.0.1:1000".parse().unwrap();
+
+        let deadline = Deadline::new(Duration::from_millis(10));
+        assert!(guarded_timeout(&breaker, deadline).is_err());
+        assert_eq!(breaker.state(server), CircuitState::Closed);
+
+        let deadline = Deadline::new(Duration::from_secs(3600));
+        assert!(guarded_timeout(&breaker, deadline).is_err());
+        assert_eq!(breaker.state(server), CircuitState::Open);
+    }
+
+    #[test]
+    fn hedged_retries_transient_errors_immediately() {
+        let (future, is_hedged) = hedged(failed(ErrorKind::Unavailable), Box::new(future::ok(1)));
+        assert_eq!(future.wait().ok(), Some(1));
+        assert!(is_hedged.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn hedged_returns_other_errors_immediately() {
+        let (future, is_hedged) =
+            hedged(failed(ErrorKind::Unexpected(None)), Box::new(future::ok(1)));
+        let e = future.wait().err().unwrap();
+        assert_eq!(*e.kind(), ErrorKind::Unexpected(None));
+        assert!(!is_hedged.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn hedged_returns_primary_error_if_both_fail() {
+        let (future, _) = hedged(failed(ErrorKind::Timeout), failed(ErrorKind::Other));
+        let e = future.wait().err().unwrap();
+        assert_eq!(*e.kind(), ErrorKind::Timeout);
+    }
+}
@@ -0,0 +1,294 @@
+//! 複数のサーバへの要求の振り分け。
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use {ErrorKind, Result};
+
+/// 通信に失敗したサーバを、振り分け対象から外しておく期間。
+const UNHEALTHY_PERIOD: Duration = Duration::from_secs(5);
+
+/// 要求の振り分け方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Balancing {
+    /// 各サーバに順番に振り分ける。
+    RoundRobin,
+
+    /// 応答待ちの要求が最も少ないサーバに振り分ける。
+    LeastOutstanding,
+}
+#[allow(clippy::derivable_impls)]
+impl Default for Balancing {
+    fn default() -> Self {
+        Balancing::RoundRobin
+    }
+}
+
+/// サーバの状態。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerStatus {
+    /// サーバのアドレス。
+    pub addr: SocketAddr,
+
+    /// 応答待ちの要求の数。
+    pub outstanding: usize,
+
+    /// 連続して通信に失敗した回数。
+    pub consecutive_failures: usize,
+
+    /// サーバが振り分け対象となっているかどうか。
+    ///
+    /// 通信に失敗したサーバは、一定期間が経過するか通信に成功するまで振り分け対象から外される。
+    pub is_healthy: bool,
+}
+
+#[derive(Debug)]
+struct ServerState {
+    addr: SocketAddr,
+    outstanding: usize,
+    consecutive_failures: usize,
+    unhealthy_until: Option<Instant>,
+}
+impl ServerState {
+    fn is_healthy(&self, now: Instant) -> bool {
+        match self.unhealthy_until {
+            Some(until) => until <= now,
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    servers: Vec<ServerState>,
+    next: usize,
+}
+
+/// 要求の振り分け先となるサーバ群。
+///
+/// `clone`されたインスタンス間で、各サーバの状態は共有される。
+#[derive(Debug, Clone)]
+pub(super) struct ServerPool {
+    inner: Arc<Mutex<Inner>>,
+    balancing: Balancing,
+}
+impl ServerPool {
+    pub fn new(servers: Vec<SocketAddr>, balancing: Balancing) -> Result<Self> {
+        track_assert!(!servers.is_empty(), ErrorKind::InvalidInput, "No servers");
+        let servers = servers
+            .into_iter()
+            .map(|addr| ServerState {
+                addr,
+                outstanding: 0,
+                consecutive_failures: 0,
+                unhealthy_until: None,
+            })
+            .collect();
+        Ok(ServerPool {
+            inner: Arc::new(Mutex::new(Inner { servers, next: 0 })),
+            balancing,
+        })
+    }
+
+    pub fn balancing(&self) -> Balancing {
+        self.balancing
+    }
+
+    pub fn set_balancing(&mut self, balancing: Balancing) {
+        self.balancing = balancing;
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().expect("Never fails").servers.len()
+    }
+
+    pub fn statuses(&self) -> Vec<ServerStatus> {
+        let now = Instant::now();
+        let inner = self.inner.lock().expect("Never fails");
+        inner
+            .servers
+            .iter()
+            .map(|s| ServerStatus {
+                addr: s.addr,
+                outstanding: s.outstanding,
+                consecutive_failures: s.consecutive_failures,
+                is_healthy: s.is_healthy(now),
+            })
+            .collect()
+    }
+
+    /// `excludes`に含まれないサーバの中から、要求の送信先を選択する。
+    ///
+    /// 振り分け対象のサーバが存在しない場合には、通信に失敗したサーバも候補となる。
+    /// 候補が一つも存在しない場合には`None`が返される。
+    pub fn select(&self, excludes: &[SocketAddr]) -> Option<SocketAddr> {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().expect("Never fails");
+        let candidates = {
+            let healthy = (0..inner.servers.len())
+                .filter(|&i| {
+                    let s = &inner.servers[i];
+                    s.is_healthy(now) && !excludes.contains(&s.addr)
+                })
+                .collect::<Vec<_>>();
+            if healthy.is_empty() {
+                (0..inner.servers.len())
+                    .filter(|&i| !excludes.contains(&inner.servers[i].addr))
+                    .collect()
+            } else {
+                healthy
+            }
+        };
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let start = inner.next;
+        inner.next = inner.next.wrapping_add(1);
+        let offset = start % candidates.len();
+        let i = match self.balancing {
+            Balancing::RoundRobin => candidates[offset],
+            Balancing::LeastOutstanding => {
+                // NOTE: 同数のサーバ間で偏りが生じないように、走査の開始位置をずらしている
+                *candidates[offset..]
+                    .iter()
+                    .chain(candidates[..offset].iter())
+                    .min_by_key(|&&i| inner.servers[i].outstanding)
+                    .expect("Never fails")
+            }
+        };
+        Some(inner.servers[i].addr)
+    }
+
+    /// `server`への要求の送信を記録する。
+    ///
+    /// 返された`Outstanding`が破棄されるまで、要求は応答待ちとして扱われる。
+    pub fn start(&self, server: SocketAddr) -> Outstanding {
+        self.with_server(server, |s| s.outstanding += 1);
+        Outstanding {
+            pool: self.clone(),
+            server,
+        }
+    }
+
+    pub fn record_success(&self, server: SocketAddr) {
+        self.with_server(server, |s| {
+            s.consecutive_failures = 0;
+            s.unhealthy_until = None;
+        });
+    }
+
+    pub fn record_failure(&self, server: SocketAddr) {
+        self.with_server(server, |s| {
+            s.consecutive_failures += 1;
+            s.unhealthy_until = Some(Instant::now() + UNHEALTHY_PERIOD);
+        });
+    }
+
+    fn with_server<F>(&self, server: SocketAddr, f: F)
+    where
+        F: FnOnce(&mut ServerState),
+    {
+        let mut inner = self.inner.lock().expect("Never fails");
+        if let Some(s) = inner.servers.iter_mut().find(|s| s.addr == server) {
+            f(s);
+        }
+    }
+}
+
+/// 応答待ちの要求を表す。
+#[derive(Debug)]
+pub(super) struct Outstanding {
+    pool: ServerPool,
+    server: SocketAddr,
+}
+impl Outstanding {
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+}
+impl Drop for Outstanding {
+    fn drop(&mut self) {
+        self.pool.with_server(self.server, |s| {
+            s.outstanding = s.outstanding.saturating_sub(1);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs() -> Vec<SocketAddr> {
+        (1..4)
+            .map(|i| format!("127.0.0.1:{}", i).parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn round_robin_works() {
+        let servers = addrs();
+        let pool = ServerPool::new(servers.clone(), Balancing::RoundRobin).unwrap();
+        let selected = (0..6)
+            .map(|_| pool.select(&[]).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(selected[..3], servers[..]);
+        assert_eq!(selected[3..], servers[..]);
+
+        assert!(ServerPool::new(Vec::new(), Balancing::RoundRobin).is_err());
+    }
+
+    #[test]
+    fn select_skips_unhealthy_servers() {
+        let servers = addrs();
+        let pool = ServerPool::new(servers.clone(), Balancing::RoundRobin).unwrap();
+        pool.record_failure(servers[0]);
+        for _ in 0..6 {
+            assert_ne!(pool.select(&[]), Some(servers[0]));
+        }
+        assert_eq!(pool.select(&servers[1..]), Some(servers[0]));
+
+        // 全てのサーバが振り分け対象外の場合には、失敗したサーバも候補となる
+        pool.record_failure(servers[1]);
+        pool.record_failure(servers[2]);
+        let selected = (0..3).map(|_| pool.select(&[])).collect::<Vec<_>>();
+        assert!(servers.iter().all(|s| selected.contains(&Some(*s))));
+        assert_eq!(pool.select(&servers), None);
+
+        // 通信に成功すると振り分け対象に戻る
+        pool.record_success(servers[0]);
+        for _ in 0..3 {
+            assert_eq!(pool.select(&[]), Some(servers[0]));
+        }
+        let status = &pool.statuses()[1];
+        assert_eq!(status.consecutive_failures, 1);
+        assert!(!status.is_healthy);
+    }
+
+    #[test]
+    fn least_outstanding_works() {
+        let servers = addrs();
+        let pool = ServerPool::new(servers.clone(), Balancing::LeastOutstanding).unwrap();
+        let a = pool.start(servers[0]);
+        let b0 = pool.start(servers[1]);
+        let b1 = pool.start(servers[1]);
+        for _ in 0..3 {
+            assert_eq!(pool.select(&[]), Some(servers[2]));
+        }
+
+        let c = pool.start(servers[2]);
+        let d = pool.start(servers[2]);
+        for _ in 0..3 {
+            assert_eq!(pool.select(&[]), Some(servers[0]));
+        }
+
+        // 応答待ちの要求が破棄されると、その分だけ数が減る
+        drop((b0, b1));
+        for _ in 0..3 {
+            assert_eq!(pool.select(&[]), Some(servers[1]));
+        }
+        assert_eq!(pool.statuses()[1].outstanding, 0);
+        drop((a, c, d));
+        assert!(pool.statuses().iter().all(|s| s.outstanding == 0));
+    }
+}
@@ -0,0 +1,148 @@
+//! RPCの再試行ポリシー。
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use ErrorKind;
+
+/// RPCの再試行ポリシー。
+///
+/// 再試行の間隔は`initial_backoff`から始まり、再試行の度に`multiplier`倍される(上限は`max_backoff`)。
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 最大試行回数(最初の試行を含む)。
+    ///
+    /// `1`の場合には再試行は行われない。
+    pub max_attempts: usize,
+
+    /// 最初の再試行までの待ち時間。
+    pub initial_backoff: Duration,
+
+    /// 再試行までの待ち時間の上限。
+    pub max_backoff: Duration,
+
+    /// 再試行の度に待ち時間に掛けられる倍率。
+    pub multiplier: u32,
+
+    /// `true`の場合には、待ち時間を`0`から算出値の間でランダムに決定する。
+    ///
+    /// 多数のクライアントが同時に再試行を行うことを避けるために使用する。
+    pub jitter: bool,
+
+    /// 再試行の対象となるエラーの種類。
+    pub retryable_errors: Vec<ErrorKind>,
+}
+impl RetryPolicy {
+    /// 再試行を一切行わないポリシーを返す。
+    pub fn no_retry() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// 指定の種類のエラーが再試行の対象かどうかを判定する。
+    pub fn is_retryable(&self, kind: &ErrorKind) -> bool {
+        self.retryable_errors.contains(kind)
+    }
+
+    /// `retry_count`回目の再試行を行う前の待ち時間を返す。
+    ///
+    /// `jitter`が`true`の場合には、呼び出し毎に異なる値が返される。
+    pub fn backoff(&self, retry_count: usize) -> Duration {
+        let mut backoff = self.initial_backoff;
+        for _ in 1..retry_count {
+            if backoff >= self.max_backoff {
+                break;
+            }
+            backoff = backoff
+                .checked_mul(self.multiplier)
+                .unwrap_or(self.max_backoff);
+        }
+        if backoff > self.max_backoff {
+            backoff = self.max_backoff;
+        }
+        if self.jitter {
+            let random = RandomState::new().build_hasher().finish();
+            backoff = backoff.mul_f64((random % 1_000_001) as f64 / 1_000_000.0);
+        }
+        backoff
+    }
+}
+impl Default for RetryPolicy {
+    /// `ErrorKind::Unavailable`と`ErrorKind::Timeout`を対象とするが、再試行は行わないポリシーを返す。
+    ///
+    /// 再試行を有効にするには`max_attempts`を変更すること。
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2,
+            jitter: true,
+            retryable_errors: vec![ErrorKind::Unavailable, ErrorKind::Timeout],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(jitter: bool) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            multiplier: 2,
+            jitter,
+            retryable_errors: vec![ErrorKind::Unavailable],
+        }
+    }
+
+    #[test]
+    fn backoff_works() {
+        let p = policy(false);
+        assert_eq!(p.backoff(1), Duration::from_millis(100));
+        assert_eq!(p.backoff(2), Duration::from_millis(200));
+        assert_eq!(p.backoff(3), Duration::from_millis(400));
+        assert_eq!(p.backoff(4), Duration::from_millis(800));
+
+        // 上限を超えない
+        assert_eq!(p.backoff(5), Duration::from_secs(1));
+        assert_eq!(p.backoff(1000), Duration::from_secs(1));
+
+        let p = RetryPolicy {
+            initial_backoff: Duration::from_secs(2),
+            ..policy(false)
+        };
+        assert_eq!(p.backoff(1), Duration::from_secs(1));
+
+        // 乗算でオーバーフローする場合にも上限に丸められる
+        let p = RetryPolicy {
+            multiplier: u32::MAX,
+            max_backoff: Duration::from_secs(u64::MAX),
+            ..policy(false)
+        };
+        assert_eq!(p.backoff(10), Duration::from_secs(u64::MAX));
+    }
+
+    #[test]
+    fn backoff_with_jitter_works() {
+        let p = policy(true);
+        for retry_count in 1..8 {
+            let cap = policy(false).backoff(retry_count);
+            for _ in 0..100 {
+                assert!(p.backoff(retry_count) <= cap);
+            }
+        }
+    }
+
+    #[test]
+    fn is_retryable_works() {
+        let p = policy(false);
+        assert!(p.is_retryable(&ErrorKind::Unavailable));
+        assert!(!p.is_retryable(&ErrorKind::Timeout));
+        assert_eq!(RetryPolicy::no_retry().max_attempts, 1);
+    }
+}
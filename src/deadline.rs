@@ -1,17 +1,56 @@
 //! RPC処理のデッドライン。
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use time::saturating_add;
 
 /// RPC処理のデッドラインを表現するための構造体。
-#[derive(Debug, Clone, Copy)]
-pub struct Deadline(Duration);
+///
+/// デッドラインは絶対時刻として保持されるため、
+/// 同じインスタンスを複数の要求(e.g., リーダの問い合わせや再試行)で使い回すと、
+/// 経過時間の分だけ残り時間が減っていく。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline(Instant);
 impl Deadline {
-    /// 新しい`Deadline`インスタンスを生成する。
+    /// 現在時刻から`duration`後をデッドラインとする`Deadline`インスタンスを生成する。
+    ///
+    /// `Instant`で表現できないほど`duration`が大きい場合には、表現可能な最も遠い時刻に丸められる。
     pub fn new(duration: Duration) -> Self {
-        Deadline(duration)
+        Deadline(saturating_add(Instant::now(), duration, |t, d| {
+            t.checked_add(d)
+        }))
     }
 
-    /// デッドラインを`Duration`に変換して返す。
-    pub fn as_duration(&self) -> Duration {
+    /// 指定時刻をデッドラインとする`Deadline`インスタンスを生成する。
+    pub fn at(instant: Instant) -> Self {
+        Deadline(instant)
+    }
+
+    /// デッドラインの時刻を返す。
+    pub fn instant(&self) -> Instant {
         self.0
     }
+
+    /// デッドラインまでの残り時間を返す。
+    ///
+    /// 既にデッドラインを過ぎている場合には`0`が返される。
+    pub fn remaining(&self) -> Duration {
+        let now = Instant::now();
+        if self.0 > now {
+            self.0 - now
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    /// デッドラインまでの残り時間を返す。
+    ///
+    /// `remaining`の別名であり、デッドラインが`Duration`で表現されていた頃との互換性のために提供されている。
+    pub fn as_duration(&self) -> Duration {
+        self.remaining()
+    }
+
+    /// デッドラインを過ぎているかどうかを判定する。
+    pub fn is_expired(&self) -> bool {
+        self.0 <= Instant::now()
+    }
 }
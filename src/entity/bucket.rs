@@ -1,7 +1,11 @@
 //! バケツ関連のエンティティ定義。
+use siphasher::sip::SipHasher13;
 use std::cmp;
+use std::hash::{Hash, Hasher};
 
 use entity::device::DeviceId;
+use entity::object::ObjectId;
+use {ErrorKind, Result};
 
 // FIXME: 構造体に置き換える
 /// バケツのID。
@@ -96,6 +100,28 @@ impl Bucket {
         }
     }
 
+    /// オブジェクトが割り当てられるセグメントの番号を返す。
+    ///
+    /// frugalosサーバの`Bucket::get_segment`と同様に、
+    /// オブジェクトIDのハッシュ値（SipHash-1-3、鍵はゼロ）をセグメント数で割った余りを返す。
+    /// そのため、結果はセグメント単位のRPCの宛先を決めるために使用できる。
+    ///
+    /// セグメント数が確定していない（i.e., `0`の）場合には`ErrorKind::InvalidInput`が返される。
+    pub fn segment_of(&self, object_id: &ObjectId) -> Result<u16> {
+        let segment_count = self.segment_count();
+        track_assert_ne!(
+            segment_count,
+            0,
+            ErrorKind::InvalidInput,
+            "Segment count is not fixed: bucket={:?}",
+            self.id()
+        );
+
+        let mut hasher = SipHasher13::new();
+        object_id.hash(&mut hasher);
+        Ok((hasher.finish() % u64::from(segment_count)) as u16)
+    }
+
     /// バケツのデバイスグループサイズ（i.e., Raftのクラスタサイズ）を返す。
     pub fn device_group_size(&self) -> u8 {
         // FIXME: キャストではなく生成時にバリデーションする
@@ -243,3 +269,58 @@ pub struct DispersedBucket {
     /// ErasureCodingのデータフラグメント数。
     pub data_fragment_count: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(segment_count: u32) -> Bucket {
+        Bucket::Dispersed(DispersedBucket {
+            id: "test".to_owned(),
+            seqno: 0,
+            device: "dev".to_owned(),
+            segment_count,
+            tolerable_faults: 1,
+            data_fragment_count: 4,
+        })
+    }
+
+    #[test]
+    fn segment_of_works() {
+        let b = bucket(1);
+        assert_eq!(b.segment_of(&"foo".to_owned()).ok(), Some(0));
+        assert_eq!(b.segment_of(&"bar".to_owned()).ok(), Some(0));
+
+        // NOTE: frugalosサーバ側の割り当てと互換性を保つため、値を変更してはならない
+        //
+        // 期待値は、サーバと同じ入力（オブジェクトIDのバイト列に`0xff`を付加したもの）を
+        // SipHash-1-3の参照実装でハッシュして得たもの。
+        let b = bucket(100);
+        assert_eq!(b.segment_of(&"".to_owned()).ok(), Some(19));
+        assert_eq!(b.segment_of(&"foo".to_owned()).ok(), Some(99));
+        assert_eq!(b.segment_of(&"bar".to_owned()).ok(), Some(7));
+        assert_eq!(b.segment_of(&"0123456789".to_owned()).ok(), Some(9));
+        assert_eq!(b.segment_of(&"日本語".to_owned()).ok(), Some(38));
+        assert_eq!(
+            b.segment_of(&"object/0000000000000001".to_owned()).ok(),
+            Some(61)
+        );
+
+        let b = bucket(1000);
+        assert_eq!(b.segment_of(&"".to_owned()).ok(), Some(119));
+        assert_eq!(b.segment_of(&"foo".to_owned()).ok(), Some(999));
+        assert_eq!(b.segment_of(&"bar".to_owned()).ok(), Some(207));
+        assert_eq!(b.segment_of(&"0123456789".to_owned()).ok(), Some(109));
+        assert_eq!(b.segment_of(&"日本語".to_owned()).ok(), Some(438));
+        assert_eq!(
+            b.segment_of(&"object/0000000000000001".to_owned()).ok(),
+            Some(361)
+        );
+    }
+
+    #[test]
+    fn segment_of_fails_if_segment_count_is_not_fixed() {
+        let b = bucket(0);
+        assert!(b.segment_of(&"foo".to_owned()).is_err());
+    }
+}
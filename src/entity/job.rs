@@ -0,0 +1,28 @@
+//! サーバ側で非同期に実行されるジョブ関連のエンティティ定義。
+
+/// ジョブのID。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct JobId(pub u64);
+
+/// ジョブの状態。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// 実行中。
+    Running,
+
+    /// 正常に完了した。
+    Completed,
+
+    /// キャンセルされた。
+    Cancelled,
+
+    /// エラーにより中断された。
+    Failed,
+}
+impl JobState {
+    /// ジョブが終了しているかどうかを返す。
+    pub fn is_finished(self) -> bool {
+        self != JobState::Running
+    }
+}
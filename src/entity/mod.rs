@@ -1,6 +1,7 @@
 //! エンティティ定義。
 pub mod bucket;
 pub mod device;
+pub mod job;
 pub mod node;
 pub mod object;
 pub mod server;
@@ -1,5 +1,10 @@
 //! オブジェクト関連のエンティティ定義。
+use std::collections::BTreeMap;
 use std::str::FromStr;
+
+use checksum::Checksum;
+use entity::job::JobState;
+use time::{Seconds, UnixTimestamp};
 use Error;
 
 // FIXME: 構造体にする
@@ -20,6 +25,69 @@ impl FromStr for ObjectVersion {
     }
 }
 
+/// オブジェクトの有効期限.
+///
+/// 有効期限を過ぎたオブジェクトは、サーバによって削除される.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Expiry {
+    /// 指定時刻に失効する.
+    At(UnixTimestamp),
+
+    /// 保存されてから指定秒数が経過した時点で失効する.
+    After(Seconds),
+}
+
+/// ユーザ定義のオブジェクトの属性値.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AttributeValue {
+    /// 文字列.
+    String(String),
+
+    /// 整数.
+    Integer(i64),
+
+    /// 真偽値.
+    Boolean(bool),
+
+    /// 任意のバイト列.
+    Bytes(Vec<u8>),
+}
+
+/// ユーザ定義のオブジェクトの属性群.
+///
+/// コンテントタイプやアプリケーション固有のタグ等を、オブジェクトの内容とは別に保持するために使用する.
+pub type Attributes = BTreeMap<String, AttributeValue>;
+
+/// 属性によるオブジェクトの絞り込み条件.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeFilter {
+    /// 属性のキー.
+    pub key: String,
+
+    /// 属性の値.
+    ///
+    /// `None`の場合には、値に関わらずキーを持つオブジェクトが対象となる.
+    pub value: Option<AttributeValue>,
+}
+
+/// オブジェクトの付帯情報.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ObjectInfo {
+    /// バージョン番号.
+    pub version: ObjectVersion,
+
+    /// 有効期限.
+    ///
+    /// 期限が設定されていない場合には`None`となる.
+    pub expires_at: Option<UnixTimestamp>,
+
+    /// ユーザ定義の属性群.
+    pub attributes: Attributes,
+
+    /// 保存時に指定されたオブジェクトの内容のチェックサム.
+    pub checksum: Option<Checksum>,
+}
+
 /// メタデータオブジェクトの接頭辞
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ObjectPrefix(pub String);
@@ -32,6 +100,58 @@ pub struct ObjectSummary {
     pub version: ObjectVersion,
 }
 
+/// ページ単位で取得されたメタデータオブジェクトの要約一覧.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ObjectSummaryPage {
+    /// オブジェクトIDの昇順に並んだ要約一覧.
+    pub summaries: Vec<ObjectSummary>,
+
+    /// 次のページを取得する際に`start_after`として指定するオブジェクトID.
+    ///
+    /// 最後のページの場合には`None`となる.
+    pub next: Option<ObjectId>,
+}
+
+/// オブジェクトに対する変更の種類.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectEventKind {
+    /// オブジェクトが保存された.
+    Put,
+
+    /// オブジェクトが削除された.
+    Delete,
+}
+
+/// オブジェクトに対する変更の通知.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectEvent {
+    /// 変更されたオブジェクトのID.
+    pub object_id: ObjectId,
+
+    /// 変更前のバージョン.
+    ///
+    /// 新規に保存された場合には`None`となる.
+    pub old_version: Option<ObjectVersion>,
+
+    /// 変更後のバージョン.
+    ///
+    /// 削除された場合には`None`となる.
+    pub new_version: Option<ObjectVersion>,
+
+    /// 変更の種類.
+    pub kind: ObjectEventKind,
+}
+
+/// 変更通知の取得結果.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ObjectEvents {
+    /// 発生順に並んだ変更通知.
+    pub events: Vec<ObjectEvent>,
+
+    /// 次回の取得時に`since`として指定するバージョン.
+    pub next: ObjectVersion,
+}
+
 /// オブジェクトのメタデータ.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Metadata {
@@ -42,6 +162,33 @@ pub struct Metadata {
     pub data: Vec<u8>,
 }
 
+/// 分割アップロードのセッションID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct UploadId(pub u64);
+
+/// オブジェクトの内容の断片.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectChunk {
+    /// オブジェクトのバージョン.
+    pub version: ObjectVersion,
+
+    /// オブジェクト全体のバイト数.
+    pub object_size: u64,
+
+    /// オブジェクトの先頭から見た断片の開始位置.
+    pub offset: u64,
+
+    /// 断片のデータ.
+    pub data: Vec<u8>,
+
+    /// オブジェクト全体の内容のチェックサム.
+    ///
+    /// 全ての断片に同じ値が含まれるため、受信側は断片を受け取る度に逐次的に計算し、
+    /// 最後の断片を受け取った時点で検証できる.
+    /// オブジェクトにチェックサムが付与されていない場合には`None`となる.
+    pub checksum: Option<Checksum>,
+}
+
 /// 接頭辞指定でのオブジェクト削除時の削除結果要約
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct DeleteObjectsByPrefixSummary {
@@ -51,6 +198,26 @@ pub struct DeleteObjectsByPrefixSummary {
     pub total: u64,
 }
 
+/// 接頭辞指定でのオブジェクト削除ジョブの進捗
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteObjectsByPrefixProgress {
+    /// ジョブの状態
+    pub state: JobState,
+
+    /// ジョブの開始から現時点までに削除されたオブジェクトの数
+    ///
+    /// ジョブの実行中は、削除が進むにつれて増加する。
+    /// `DeleteObjectsByPrefixSummary::total`と同じく、JSON で u64 を扱えるように string に serialize する。
+    #[serde(with = "prefix_summary_total")]
+    pub deleted: u64,
+
+    /// 削除処理が完了していないセグメントの数
+    pub remaining_segments: u16,
+
+    /// 削除処理中に発生したエラー
+    pub errors: Vec<Error>,
+}
+
 /// フラグメント要約.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct FragmentsSummary {
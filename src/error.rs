@@ -32,5 +32,8 @@ pub enum ErrorKind {
     NotLeader,
     Unexpected(Option<ObjectVersion>),
     Other,
+
+    /// オブジェクトの内容がチェックサムと一致しない。
+    ChecksumMismatch,
 }
 impl TrackableErrorKind for ErrorKind {}
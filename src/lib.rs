@@ -3,11 +3,16 @@
 //! [frugalos]: https://github.com/frugalos/frugalos
 #![warn(missing_docs)]
 extern crate bytecodec;
+extern crate crc32c;
 extern crate fibers;
 extern crate fibers_rpc;
 extern crate futures;
+#[cfg(feature = "compat")]
+extern crate futures03;
 extern crate libc;
 extern crate serde;
+extern crate sha2;
+extern crate siphasher;
 
 #[macro_use]
 extern crate serde_derive;
@@ -16,6 +21,7 @@ extern crate trackable;
 
 pub use error::{Error, ErrorKind};
 
+pub mod checksum;
 pub mod client;
 pub mod consistency;
 pub mod deadline;
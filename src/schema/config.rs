@@ -5,6 +5,7 @@ use std::net::SocketAddr;
 
 use entity::bucket::{Bucket, BucketId, BucketSummary};
 use entity::device::{Device, DeviceId, DeviceSummary};
+use entity::node::RemoteNodeId;
 use entity::server::{Server, ServerId, ServerSummary};
 use Result;
 
@@ -200,6 +201,24 @@ impl Call for DeleteBucketRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// セグメントを担当するMDSノード一覧取得RPC。
+///
+/// 対象のバケツやセグメントが存在しない場合には`None`が返される。
+#[derive(Debug)]
+pub struct GetSegmentNodesRpc;
+impl Call for GetSegmentNodesRpc {
+    const ID: ProcedureId = ProcedureId(0x0004_0004);
+    const NAME: &'static str = "frugalos.config.bucket.segment_nodes";
+
+    type Req = SegmentRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<Vec<RemoteNodeId>>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// Raftのリーダノード取得RPC。
 // NOTE: リーダ選出中の場合にはserver側でwaitする
 #[derive(Debug)]
@@ -216,3 +235,11 @@ impl Call for GetLeaderRpc {
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
+
+/// セグメント単位でのRPC要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentRequest {
+    pub bucket_id: BucketId,
+    pub segment: u16,
+}
@@ -1,20 +1,24 @@
 //! frugalosの公開API系RPCのスキーマ定義。
 use bytecodec::bincode_codec::{BincodeDecoder, BincodeEncoder};
-use fibers_rpc::{Call, ProcedureId};
-use std::collections::BTreeSet;
+use fibers_rpc::{Call, Cast, ProcedureId};
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Range;
 use std::time::Duration;
 
+use checksum::Checksum;
 use consistency::ReadConsistency;
 use entity::bucket::BucketId;
 use entity::device::DeviceId;
+use entity::job::JobId;
 use entity::object::{
-    DeleteObjectsByPrefixSummary, FragmentsSummary, ObjectId, ObjectPrefix, ObjectSummary,
-    ObjectVersion,
+    AttributeFilter, Attributes, DeleteObjectsByPrefixProgress, DeleteObjectsByPrefixSummary,
+    Expiry, FragmentsSummary, ObjectChunk, ObjectEvent, ObjectId, ObjectInfo, ObjectPrefix,
+    ObjectSummary, ObjectSummaryPage, ObjectVersion, UploadId,
 };
 use expect::Expect;
 use multiplicity::MultiplicityConfig;
 use repair::RepairConfig;
+use time::UnixTimestamp;
 use Result;
 
 /// オブジェクト取得RPC。
@@ -50,6 +54,43 @@ impl Call for HeadObjectRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// オブジェクト取得RPC。
+///
+/// `GetObjectRpc`とは異なり、バージョンに加えて有効期限等のオブジェクトの情報も返す。
+#[derive(Debug)]
+pub struct GetObjectInfoRpc;
+impl Call for GetObjectInfoRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0025);
+    const NAME: &'static str = "frugalos.object.get_with_info";
+
+    type Req = ObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    // FIXME: データが巨大になる可能性があるのでbincodeはやめる
+    type Res = Result<Option<(ObjectInfo, Vec<u8>)>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// オブジェクト存在確認RPC。
+///
+/// `HeadObjectRpc`とは異なり、バージョンに加えて有効期限等のオブジェクトの情報も返す。
+#[derive(Debug)]
+pub struct HeadObjectInfoRpc;
+impl Call for HeadObjectInfoRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0026);
+    const NAME: &'static str = "frugalos.object.head_with_info";
+
+    type Req = HeadObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<ObjectInfo>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト保存RPC。
 #[derive(Debug)]
 pub struct PutObjectRpc;
@@ -67,6 +108,25 @@ impl Call for PutObjectRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// オプション指定付きのオブジェクト保存RPC。
+///
+/// `PutObjectRpc`とは異なり、有効期限等のオプションを指定できる。
+#[derive(Debug)]
+pub struct PutObjectWithOptionsRpc;
+impl Call for PutObjectWithOptionsRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0027);
+    const NAME: &'static str = "frugalos.object.put_with_options";
+
+    // FIXME: データが巨大になる可能性があるのでbincodeはやめる
+    type Req = PutObjectWithOptionsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<(ObjectVersion, bool)>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト削除RPC。
 #[derive(Debug)]
 pub struct DeleteObjectRpc;
@@ -161,6 +221,29 @@ impl Call for DeleteObjectsByRangeRpc {
     }
 }
 
+/// バージョンの範囲指定でのオブジェクト一覧取得RPC。
+///
+/// `DeleteObjectsByRangeRpc`の参照版であり、オブジェクトは削除されない。
+/// 結果はバージョンの昇順に並び、最大で`RangePageRequest::max_count`個の要素を含む。
+#[derive(Debug)]
+pub struct ListObjectsByRangeRpc;
+impl Call for ListObjectsByRangeRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_001e);
+    const NAME: &'static str = "frugalos.object.list_by_range";
+
+    type Req = RangePageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
 /// 接頭辞削除RPC。
 #[derive(Debug)]
 pub struct DeleteObjectsByPrefixRpc;
@@ -177,6 +260,61 @@ impl Call for DeleteObjectsByPrefixRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// 接頭辞削除ジョブの開始RPC。
+///
+/// 削除処理の完了を待たずに、ジョブのIDを返す。
+#[derive(Debug)]
+pub struct StartDeleteObjectsByPrefixRpc;
+impl Call for StartDeleteObjectsByPrefixRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0022);
+    const NAME: &'static str = "frugalos.object.delete_by_prefix.start";
+
+    type Req = PrefixRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<JobId>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 接頭辞削除ジョブの進捗取得RPC。
+///
+/// ジョブが存在しない場合には`None`が返される。
+#[derive(Debug)]
+pub struct GetDeleteObjectsByPrefixProgressRpc;
+impl Call for GetDeleteObjectsByPrefixProgressRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0023);
+    const NAME: &'static str = "frugalos.object.delete_by_prefix.progress";
+
+    type Req = JobId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<DeleteObjectsByPrefixProgress>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 接頭辞削除ジョブのキャンセルRPC。
+///
+/// キャンセル要求時点での進捗を返す。ジョブが存在しない場合には`None`が返される。
+/// キャンセル前に削除されたオブジェクトは元に戻らない。
+#[derive(Debug)]
+pub struct CancelDeleteObjectsByPrefixRpc;
+impl Call for CancelDeleteObjectsByPrefixRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0024);
+    const NAME: &'static str = "frugalos.object.delete_by_prefix.cancel";
+
+    type Req = JobId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<DeleteObjectsByPrefixProgress>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// An RPC for deleting objects physically.
 #[derive(Debug)]
 pub struct DeleteObjectSetFromDeviceRpc;
@@ -229,9 +367,348 @@ impl Call for CountFragmentsRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// 分割アップロード開始RPC。
+#[derive(Debug)]
+pub struct StartPutObjectRpc;
+impl Call for StartPutObjectRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_000e);
+    const NAME: &'static str = "frugalos.object.put.start";
+
+    type Req = StartPutObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<UploadId>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 分割アップロードの断片送信RPC。
+#[derive(Debug)]
+pub struct PutObjectChunkRpc;
+impl Call for PutObjectChunkRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_000f);
+    const NAME: &'static str = "frugalos.object.put.chunk";
+
+    type Req = PutObjectChunkRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<()>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 分割アップロード完了RPC。
+///
+/// 送信済みの断片群を一つのオブジェクトとして保存する。
+#[derive(Debug)]
+pub struct CompletePutObjectRpc;
+impl Call for CompletePutObjectRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0010);
+    const NAME: &'static str = "frugalos.object.put.complete";
+
+    type Req = CompletePutObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<(ObjectVersion, bool)>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 分割アップロード中断RPC。
+#[derive(Debug)]
+pub struct AbortPutObjectRpc;
+impl Cast for AbortPutObjectRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0011);
+    const NAME: &'static str = "frugalos.object.put.abort";
+
+    type Notification = UploadId;
+    type Decoder = BincodeDecoder<Self::Notification>;
+    type Encoder = BincodeEncoder<Self::Notification>;
+}
+
+/// オブジェクトの断片取得RPC。
+#[derive(Debug)]
+pub struct GetObjectChunkRpc;
+impl Call for GetObjectChunkRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0012);
+    const NAME: &'static str = "frugalos.object.get_chunk";
+
+    type Req = ObjectChunkRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<ObjectChunk>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バイト範囲指定でのオブジェクト取得RPC。
+#[derive(Debug)]
+pub struct GetObjectRangeRpc;
+impl Call for GetObjectRangeRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0013);
+    const NAME: &'static str = "frugalos.object.get_range";
+
+    type Req = ObjectRangeRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<ObjectChunk>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// ページ単位でのオブジェクト一覧取得RPC。
+#[derive(Debug)]
+pub struct ListObjectsPageRpc;
+impl Call for ListObjectsPageRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0014);
+    const NAME: &'static str = "frugalos.object.list_page";
+
+    type Req = ListObjectsPageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ObjectSummaryPage>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 接頭辞指定でのページ単位のオブジェクト一覧取得RPC。
+#[derive(Debug)]
+pub struct ListObjectsByPrefixPageRpc;
+impl Call for ListObjectsByPrefixPageRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0015);
+    const NAME: &'static str = "frugalos.object.list_by_prefix_page";
+
+    type Req = PrefixPageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ObjectSummaryPage>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 複数オブジェクトの一括保存RPC。
+///
+/// 結果は要求と同じ順序で、オブジェクト毎に返される。
+#[derive(Debug)]
+pub struct BatchPutObjectsRpc;
+impl Call for BatchPutObjectsRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0016);
+    const NAME: &'static str = "frugalos.object.batch_put";
+
+    type Req = BatchPutObjectsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<Result<(ObjectVersion, bool)>>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_request(_: &Self::Req) -> bool {
+        true
+    }
+}
+
+/// 複数オブジェクトの一括取得RPC。
+///
+/// 結果は要求と同じ順序で、オブジェクト毎に返される。
+#[derive(Debug)]
+pub struct BatchGetObjectsRpc;
+impl Call for BatchGetObjectsRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0017);
+    const NAME: &'static str = "frugalos.object.batch_get";
+
+    type Req = BatchGetObjectsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<Result<Option<(ObjectInfo, Vec<u8>)>>>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
+/// 複数オブジェクトの一括削除RPC。
+///
+/// 結果は要求と同じ順序で、オブジェクト毎に返される。
+#[derive(Debug)]
+pub struct BatchDeleteObjectsRpc;
+impl Call for BatchDeleteObjectsRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0018);
+    const NAME: &'static str = "frugalos.object.batch_delete";
+
+    type Req = BatchDeleteObjectsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<Result<Option<ObjectVersion>>>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツ単位でのオブジェクトの変更通知取得RPC。
+///
+/// 変更が存在しない場合には、変更が発生するか`WatchObjectsRequest::timeout`が
+/// 経過するまでサーバ側で待機する。
+#[derive(Debug)]
+pub struct WatchObjectsRpc;
+impl Call for WatchObjectsRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0019);
+    const NAME: &'static str = "frugalos.object.watch";
+
+    type Req = WatchObjectsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<BucketEvents>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 有効期限が近いオブジェクトの一覧取得RPC。
+#[derive(Debug)]
+pub struct ListExpiringObjectsRpc;
+impl Call for ListExpiringObjectsRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_001a);
+    const NAME: &'static str = "frugalos.object.list_expiring";
+
+    type Req = ExpiringObjectsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
+/// 属性指定でのオブジェクト一覧取得RPC。
+#[derive(Debug)]
+pub struct ListObjectsByAttributeRpc;
+impl Call for ListObjectsByAttributeRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_001b);
+    const NAME: &'static str = "frugalos.object.list_by_attribute";
+
+    type Req = AttributeRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
+/// サーバ側でのオブジェクトのコピーRPC。
+///
+/// 異なるバケツ間でのコピーも可能。
+/// コピー元が存在しない場合には`None`が返される。
+#[derive(Debug)]
+pub struct CopyObjectRpc;
+impl Call for CopyObjectRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_001c);
+    const NAME: &'static str = "frugalos.object.copy";
+
+    type Req = CopyObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<(ObjectVersion, bool)>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// サーバ側でのオブジェクトの名前変更RPC。
+///
+/// コピー先への保存に成功した後に、コピー時のバージョンを指定してコピー元を削除する。
+/// コピー元の削除に失敗した場合には、コピー先への保存は取り消される。
+/// コピー元が存在しない場合には`None`が返される。
+#[derive(Debug)]
+pub struct RenameObjectRpc;
+impl Call for RenameObjectRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_001d);
+    const NAME: &'static str = "frugalos.object.rename";
+
+    type Req = CopyObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<(ObjectVersion, bool)>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// `DeleteObjectsByPrefixRpc`で削除されるオブジェクトの数を、削除せずに返すRPC。
+#[derive(Debug)]
+pub struct PreviewDeleteObjectsByPrefixRpc;
+impl Call for PreviewDeleteObjectsByPrefixRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_001f);
+    const NAME: &'static str = "frugalos.object.preview_delete_by_prefix";
+
+    type Req = PrefixRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<DeleteObjectsByPrefixSummary>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// `DeleteObjectsByRangeRpc`で削除されるオブジェクトの一覧を、削除せずに返すRPC。
+#[derive(Debug)]
+pub struct PreviewDeleteObjectsByRangeRpc;
+impl Call for PreviewDeleteObjectsByRangeRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0020);
+    const NAME: &'static str = "frugalos.object.preview_delete_by_range";
+
+    type Req = RangeRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
+/// An RPC for listing objects that `DeleteObjectSetFromDeviceRpc` would delete,
+/// without deleting them.
+#[derive(Debug)]
+pub struct PreviewDeleteObjectSetFromDeviceRpc;
+impl Call for PreviewDeleteObjectSetFromDeviceRpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0021);
+    const NAME: &'static str = "frugalos.object.preview_delete_object_set_from_device";
+
+    type Req = DeleteObjectSetFromDeviceRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<BTreeSet<ObjectId>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト単位のRPC要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectRequest {
     pub bucket_id: BucketId,
     pub object_id: ObjectId,
@@ -242,7 +719,7 @@ pub struct ObjectRequest {
 
 /// フラグメントカウント RPC 要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CountFragmentsRequest {
     pub bucket_id: BucketId,
     pub object_id: ObjectId,
@@ -253,7 +730,7 @@ pub struct CountFragmentsRequest {
 
 /// オブジェクト単位の存在確認 RPC 要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HeadObjectRequest {
     pub bucket_id: BucketId,
     pub object_id: ObjectId,
@@ -266,7 +743,7 @@ pub struct HeadObjectRequest {
 
 /// バージョン単位のRPC要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionRequest {
     pub bucket_id: BucketId,
     pub segment: u16,
@@ -276,7 +753,7 @@ pub struct VersionRequest {
 
 /// バージョン範囲でのRPC要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RangeRequest {
     pub bucket_id: BucketId,
     pub segment: u16,
@@ -284,18 +761,40 @@ pub struct RangeRequest {
     pub deadline: Duration,
 }
 
+/// バージョン範囲でのページ取得要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangePageRequest {
+    pub bucket_id: BucketId,
+    pub segment: u16,
+    pub targets: Range<ObjectVersion>,
+    pub consistency: ReadConsistency,
+
+    /// 一度に返されるオブジェクトの最大数。
+    pub max_count: u32,
+}
+
 /// オブジェクトの接頭辞単位でのRPC要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrefixRequest {
     pub bucket_id: BucketId,
     pub prefix: ObjectPrefix,
     pub deadline: Duration,
 }
 
+/// オブジェクトの属性単位でのRPC要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeRequest {
+    pub bucket_id: BucketId,
+    pub filter: AttributeFilter,
+    pub deadline: Duration,
+}
+
 /// オブジェクト保存要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PutObjectRequest {
     pub bucket_id: BucketId,
     pub object_id: ObjectId,
@@ -305,25 +804,280 @@ pub struct PutObjectRequest {
     pub multiplicity_config: MultiplicityConfig,
 }
 
+/// オプション指定付きのオブジェクト保存要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutObjectWithOptionsRequest {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub content: Vec<u8>,
+    pub deadline: Duration,
+    pub expect: Expect,
+    pub multiplicity_config: MultiplicityConfig,
+
+    /// オブジェクトの有効期限。
+    pub expiry: Option<Expiry>,
+
+    /// ユーザ定義の属性群。
+    pub attributes: Attributes,
+
+    /// オブジェクトの内容のチェックサム。
+    ///
+    /// 指定された場合にはサーバ側で検証され、一致しない場合には`ErrorKind::ChecksumMismatch`となる。
+    pub checksum: Option<Checksum>,
+}
+
+/// 分割アップロードの開始要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartPutObjectRequest {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub deadline: Duration,
+    pub expect: Expect,
+    pub multiplicity_config: MultiplicityConfig,
+
+    /// オブジェクトの有効期限。
+    pub expiry: Option<Expiry>,
+
+    /// ユーザ定義の属性群。
+    pub attributes: Attributes,
+}
+
+/// 分割アップロードの断片送信要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutObjectChunkRequest {
+    pub upload_id: UploadId,
+
+    /// オブジェクトの先頭から見た断片の開始位置。
+    pub offset: u64,
+    pub data: Vec<u8>,
+}
+
+/// 分割アップロードの完了要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletePutObjectRequest {
+    pub upload_id: UploadId,
+
+    /// 送信済みの断片の合計バイト数。
+    ///
+    /// サーバ側で受信したバイト数と一致しない場合にはエラーとなる。
+    pub object_size: u64,
+
+    /// オブジェクトの内容全体のチェックサム。
+    ///
+    /// 指定された場合にはサーバ側で検証され、一致しない場合には`ErrorKind::ChecksumMismatch`となる。
+    pub checksum: Option<Checksum>,
+}
+
+/// オブジェクトの断片取得要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectChunkRequest {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub deadline: Duration,
+    pub expect: Expect,
+    pub consistency: ReadConsistency,
+
+    /// 取得する断片の開始位置。
+    pub offset: u64,
+
+    /// 取得する断片の最大バイト数。
+    pub max_size: u32,
+}
+
+/// バイト範囲指定でのオブジェクト取得要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectRangeRequest {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub deadline: Duration,
+    pub expect: Expect,
+    pub consistency: ReadConsistency,
+
+    /// 取得するバイト範囲。
+    ///
+    /// 終端がオブジェクトのサイズを超える場合には、オブジェクトの末尾までが返される。
+    /// 始端がオブジェクトのサイズを超える場合には`ErrorKind::InvalidInput`となる。
+    pub range: Range<u64>,
+}
+
+/// 一括処理要求に含まれるオブジェクト単位の操作。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectItem {
+    pub object_id: ObjectId,
+    pub expect: Expect,
+}
+
+/// 一括保存要求に含まれるオブジェクト単位の操作。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutObjectItem {
+    pub object_id: ObjectId,
+    pub content: Vec<u8>,
+    pub expect: Expect,
+
+    /// オブジェクトの有効期限。
+    pub expiry: Option<Expiry>,
+
+    /// ユーザ定義の属性群。
+    pub attributes: Attributes,
+
+    /// オブジェクトの内容のチェックサム。
+    ///
+    /// 指定された場合にはサーバ側で検証され、一致しない場合には`ErrorKind::ChecksumMismatch`となる。
+    pub checksum: Option<Checksum>,
+}
+
+/// 複数オブジェクトの一括保存要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPutObjectsRequest {
+    pub bucket_id: BucketId,
+    pub objects: Vec<PutObjectItem>,
+    pub deadline: Duration,
+    pub multiplicity_config: MultiplicityConfig,
+}
+
+/// 複数オブジェクトの一括取得要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchGetObjectsRequest {
+    pub bucket_id: BucketId,
+    pub objects: Vec<ObjectItem>,
+    pub deadline: Duration,
+    pub consistency: ReadConsistency,
+}
+
+/// 複数オブジェクトの一括削除要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchDeleteObjectsRequest {
+    pub bucket_id: BucketId,
+    pub objects: Vec<ObjectItem>,
+    pub deadline: Duration,
+}
+
+/// コピーや名前変更の対象となるオブジェクト。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectTarget {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub expect: Expect,
+}
+
+/// オブジェクトのコピーおよび名前変更要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyObjectRequest {
+    pub source: ObjectTarget,
+    pub destination: ObjectTarget,
+    pub deadline: Duration,
+    pub multiplicity_config: MultiplicityConfig,
+}
+
 /// オブジェクト一覧要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListObjectsRequest {
     pub bucket_id: BucketId,
     pub segment: u16,
     pub consistency: ReadConsistency,
 }
 
+/// ページ単位でのオブジェクト一覧要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListObjectsPageRequest {
+    pub bucket_id: BucketId,
+    pub segment: u16,
+    pub consistency: ReadConsistency,
+
+    /// このIDより後のオブジェクトが返される。
+    ///
+    /// `None`の場合には先頭から返される。
+    pub start_after: Option<ObjectId>,
+
+    /// 一ページに含まれるオブジェクトの最大数。
+    pub max_count: u32,
+}
+
+/// オブジェクトの接頭辞単位でのページ取得要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefixPageRequest {
+    pub bucket_id: BucketId,
+    pub prefix: ObjectPrefix,
+    pub deadline: Duration,
+
+    /// このIDより後のオブジェクトが返される。
+    ///
+    /// `None`の場合には先頭から返される。
+    pub start_after: Option<ObjectId>,
+
+    /// 一ページに含まれるオブジェクトの最大数。
+    pub max_count: u32,
+}
+
+/// バケツ単位でのオブジェクトの変更通知要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchObjectsRequest {
+    pub bucket_id: BucketId,
+
+    /// セグメント毎の取得開始位置。
+    ///
+    /// 各セグメントについて、指定バージョンより後に発生した変更が返される。
+    /// 含まれていないセグメントについては、要求の受信後に発生した変更のみが返される。
+    pub since: BTreeMap<u16, ObjectVersion>,
+
+    /// 一度に返される変更通知の最大数。
+    pub max_count: u32,
+
+    /// 変更が発生するまでサーバ側で待機する時間の上限。
+    pub timeout: Duration,
+}
+
+/// バケツ単位での変更通知の取得結果。
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BucketEvents {
+    /// セグメント番号と変更通知の組。
+    ///
+    /// セグメント内では発生順に並んでいる。
+    pub events: Vec<(u16, ObjectEvent)>,
+
+    /// 次回の要求で`WatchObjectsRequest::since`として指定する取得開始位置。
+    pub next: BTreeMap<u16, ObjectVersion>,
+}
+
+/// 有効期限が近いオブジェクトの一覧要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringObjectsRequest {
+    pub bucket_id: BucketId,
+    pub segment: u16,
+    pub consistency: ReadConsistency,
+
+    /// この時刻より前に失効するオブジェクトが返される。
+    pub before: UnixTimestamp,
+}
+
 /// セグメント単位でのRPC要求。
 #[allow(missing_docs)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SegmentRequest {
     pub bucket_id: BucketId,
     pub segment: u16,
 }
 
 /// This struct represents how to delete objects from a device at once.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteObjectSetFromDeviceRequest {
     /// A bucket may own the objects.
     pub bucket_id: BucketId,
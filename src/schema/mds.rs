@@ -7,9 +7,11 @@ use std::time::Duration;
 use consistency::ReadConsistency;
 use entity::node::{LocalNodeId, RemoteNodeId};
 use entity::object::{
-    DeleteObjectsByPrefixSummary, Metadata, ObjectId, ObjectPrefix, ObjectSummary, ObjectVersion,
+    AttributeFilter, Attributes, DeleteObjectsByPrefixSummary, Expiry, Metadata, ObjectEvents,
+    ObjectId, ObjectInfo, ObjectPrefix, ObjectSummary, ObjectSummaryPage, ObjectVersion,
 };
 use expect::Expect;
+use time::UnixTimestamp;
 use Result;
 
 /// Raftのリーダ取得RPC。
@@ -92,6 +94,42 @@ impl Call for HeadObjectRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// オブジェクト取得RPC。
+///
+/// `GetObjectRpc`とは異なり、メタデータに加えて有効期限等のオブジェクトの情報も返す。
+#[derive(Debug)]
+pub struct GetObjectInfoRpc;
+impl Call for GetObjectInfoRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_0015);
+    const NAME: &'static str = "frugalos.mds.object.get_with_info";
+
+    type Req = ObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<(ObjectInfo, Vec<u8>)>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// オブジェクト存在確認RPC。
+///
+/// `HeadObjectRpc`とは異なり、バージョンに加えて有効期限等のオブジェクトの情報も返す。
+#[derive(Debug)]
+pub struct HeadObjectInfoRpc;
+impl Call for HeadObjectInfoRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_0016);
+    const NAME: &'static str = "frugalos.mds.object.head_with_info";
+
+    type Req = ObjectRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<ObjectInfo>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト保存RPC。
 #[derive(Debug)]
 pub struct PutObjectRpc;
@@ -108,6 +146,24 @@ impl Call for PutObjectRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// オプション指定付きのオブジェクト保存RPC。
+///
+/// `PutObjectRpc`とは異なり、有効期限等のオプションを指定できる。
+#[derive(Debug)]
+pub struct PutObjectWithOptionsRpc;
+impl Call for PutObjectWithOptionsRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_0014);
+    const NAME: &'static str = "frugalos.mds.object.put_with_options";
+
+    type Req = PutObjectWithOptionsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<(ObjectVersion, Option<ObjectVersion>)>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト削除RPC。
 #[derive(Debug)]
 pub struct DeleteObjectRpc;
@@ -172,6 +228,29 @@ impl Call for DeleteObjectsByRangeRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// バージョン範囲指定によるオブジェクト一覧取得RPC。
+///
+/// `DeleteObjectsByRangeRpc`の参照版であり、オブジェクトは削除されない。
+/// 結果はバージョンの昇順に並び、最大で`RangePageRequest::max_count`個の要素を含む。
+#[derive(Debug)]
+pub struct ListObjectsByRangeRpc;
+impl Call for ListObjectsByRangeRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_0011);
+    const NAME: &'static str = "frugalos.mds.object.list_by_range";
+
+    type Req = RangePageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
 /// 格納済みオブジェクト数取得RPC。
 #[derive(Debug)]
 pub struct GetObjectCountRpc;
@@ -224,6 +303,152 @@ impl Call for ListObjectsByPrefixRpc {
     }
 }
 
+/// ページ単位でのオブジェクト一覧取得RPC。
+#[derive(Debug)]
+pub struct ListObjectsPageRpc;
+impl Call for ListObjectsPageRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_000b);
+    const NAME: &'static str = "frugalos.mds.object.list_page";
+
+    type Req = ListObjectsPageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ObjectSummaryPage>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 接頭辞指定でのページ単位のオブジェクト一覧取得RPC。
+#[derive(Debug)]
+pub struct ListObjectsByPrefixPageRpc;
+impl Call for ListObjectsByPrefixPageRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_000c);
+    const NAME: &'static str = "frugalos.mds.object.list_by_prefix_page";
+
+    type Req = PrefixPageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ObjectSummaryPage>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 複数オブジェクトに対する操作を不可分に適用するRPC。
+///
+/// 全ての操作の`Expect`が満たされた場合にのみ、全ての操作が適用される。
+/// いずれかの操作の`Expect`が満たされない場合には、何も適用されずに、
+/// 最初に失敗した操作の`ErrorKind::Unexpected`が返される。
+///
+/// 操作対象のオブジェクトは、全て同じセグメントに属している必要がある。
+#[derive(Debug)]
+pub struct TransactionRpc;
+impl Call for TransactionRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_000d);
+    const NAME: &'static str = "frugalos.mds.object.transaction";
+
+    type Req = TransactionRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<TransactionOutcome>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// オブジェクトの変更通知取得RPC。
+///
+/// 指定バージョン以降の変更が存在しない場合には、
+/// 変更が発生するか`WatchObjectsRequest::timeout`が経過するまでサーバ側で待機する。
+/// タイムアウトした場合には、空の変更通知一覧が返される。
+#[derive(Debug)]
+pub struct WatchObjectsRpc;
+impl Call for WatchObjectsRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_000e);
+    const NAME: &'static str = "frugalos.mds.object.watch";
+
+    type Req = WatchObjectsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ObjectEvents>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 有効期限が近いオブジェクトの一覧取得RPC。
+#[derive(Debug)]
+pub struct ListExpiringObjectsRpc;
+impl Call for ListExpiringObjectsRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_000f);
+    const NAME: &'static str = "frugalos.mds.object.list_expiring";
+
+    type Req = ExpiringObjectsRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
+/// 属性指定でのオブジェクト一覧取得RPC。
+#[derive(Debug)]
+pub struct ListObjectsByAttributeRpc;
+impl Call for ListObjectsByAttributeRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_0010);
+    const NAME: &'static str = "frugalos.mds.object.list_by_attribute";
+
+    type Req = AttributeRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
+/// `DeleteObjectsByPrefixRpc`で削除されるオブジェクトの数を、削除せずに返すRPC。
+#[derive(Debug)]
+pub struct PreviewDeleteObjectsByPrefixRpc;
+impl Call for PreviewDeleteObjectsByPrefixRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_0012);
+    const NAME: &'static str = "frugalos.mds.object.preview_delete_by_prefix";
+
+    type Req = PrefixRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<DeleteObjectsByPrefixSummary>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// `DeleteObjectsByRangeRpc`で削除されるオブジェクトの一覧を、削除せずに返すRPC。
+#[derive(Debug)]
+pub struct PreviewDeleteObjectsByRangeRpc;
+impl Call for PreviewDeleteObjectsByRangeRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_0013);
+    const NAME: &'static str = "frugalos.mds.object.preview_delete_by_range";
+
+    type Req = RangeRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト単位の要求。
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -242,6 +467,20 @@ pub struct ListObjectsRequest {
     pub consistency: ReadConsistency,
 }
 
+/// ページ単位でのオブジェクト一覧の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListObjectsPageRequest {
+    pub node_id: LocalNodeId,
+    pub consistency: ReadConsistency,
+
+    /// このIDより後のオブジェクトが返される。
+    pub start_after: Option<ObjectId>,
+
+    /// 一ページに含まれるオブジェクトの最大数。
+    pub max_count: u32,
+}
+
 /// オブジェクトカウントの要求。
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -266,6 +505,18 @@ pub struct RangeRequest {
     pub targets: Range<ObjectVersion>,
 }
 
+/// バージョン範囲でのページ取得の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangePageRequest {
+    pub node_id: LocalNodeId,
+    pub targets: Range<ObjectVersion>,
+    pub consistency: ReadConsistency,
+
+    /// 一度に返されるオブジェクトの最大数。
+    pub max_count: u32,
+}
+
 /// オブジェクトの接頭辞単位の要求。
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -274,6 +525,55 @@ pub struct PrefixRequest {
     pub prefix: ObjectPrefix,
 }
 
+/// 有効期限が近いオブジェクトの一覧の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringObjectsRequest {
+    pub node_id: LocalNodeId,
+    pub consistency: ReadConsistency,
+
+    /// この時刻より前に失効するオブジェクトが返される。
+    pub before: UnixTimestamp,
+}
+
+/// オブジェクトの接頭辞単位でのページ取得要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefixPageRequest {
+    pub node_id: LocalNodeId,
+    pub prefix: ObjectPrefix,
+
+    /// このIDより後のオブジェクトが返される。
+    pub start_after: Option<ObjectId>,
+
+    /// 一ページに含まれるオブジェクトの最大数。
+    pub max_count: u32,
+}
+
+/// オブジェクトの変更通知の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchObjectsRequest {
+    pub node_id: LocalNodeId,
+
+    /// このバージョンより後に発生した変更が返される。
+    pub since: ObjectVersion,
+
+    /// 一度に返される変更通知の最大数。
+    pub max_count: u32,
+
+    /// 変更が発生するまでサーバ側で待機する時間の上限。
+    pub timeout: Duration,
+}
+
+/// オブジェクトの属性単位の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeRequest {
+    pub node_id: LocalNodeId,
+    pub filter: AttributeFilter,
+}
+
 /// オブジェクト保存要求。
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -284,3 +584,64 @@ pub struct PutObjectRequest {
     pub expect: Expect,
     pub put_content_timeout: Duration,
 }
+
+/// オプション指定付きのオブジェクト保存要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutObjectWithOptionsRequest {
+    pub node_id: LocalNodeId,
+    pub object_id: ObjectId,
+    pub metadata: Vec<u8>,
+    pub expect: Expect,
+    pub put_content_timeout: Duration,
+
+    /// オブジェクトの有効期限。
+    pub expiry: Option<Expiry>,
+
+    /// ユーザ定義の属性群。
+    pub attributes: Attributes,
+}
+
+/// 複数オブジェクトに対する操作の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionRequest {
+    pub node_id: LocalNodeId,
+    pub operations: Vec<TransactionOperation>,
+    pub put_content_timeout: Duration,
+}
+
+/// `TransactionRequest`に含まれる個々の操作。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TransactionOperation {
+    /// オブジェクトの保存。
+    #[allow(missing_docs)]
+    Put {
+        object_id: ObjectId,
+        metadata: Vec<u8>,
+        expect: Expect,
+    },
+
+    /// オブジェクトの削除。
+    #[allow(missing_docs)]
+    Delete { object_id: ObjectId, expect: Expect },
+}
+
+/// `TransactionOperation`の適用結果。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TransactionOutcome {
+    /// オブジェクトが保存された。
+    Put {
+        /// 保存されたオブジェクトのバージョン。
+        version: ObjectVersion,
+
+        /// 上書きされたオブジェクトのバージョン。
+        old_version: Option<ObjectVersion>,
+    },
+
+    /// オブジェクトが削除された。
+    Delete {
+        /// 削除されたオブジェクトのバージョン。
+        old_version: Option<ObjectVersion>,
+    },
+}
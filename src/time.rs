@@ -1,5 +1,5 @@
 //! 時間関連の構成要素。
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// 秒単位の時間尺を表すための構造体.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -14,3 +14,79 @@ impl From<Seconds> for Duration {
         Duration::from_secs(f.0)
     }
 }
+
+/// UNIXエポックからの経過秒数で表現された時刻.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UnixTimestamp(pub u64);
+impl UnixTimestamp {
+    /// 現在時刻を返す.
+    pub fn now() -> Self {
+        Self::from(SystemTime::now())
+    }
+}
+impl From<SystemTime> for UnixTimestamp {
+    fn from(f: SystemTime) -> Self {
+        // NOTE: UNIXエポックより前の時刻はエポックとして扱う
+        let elapsed = f.duration_since(UNIX_EPOCH).unwrap_or_default();
+        UnixTimestamp(elapsed.as_secs())
+    }
+}
+impl From<UnixTimestamp> for SystemTime {
+    fn from(f: UnixTimestamp) -> Self {
+        // NOTE: `SystemTime`で表現できない時刻は、表現可能な範囲に丸める
+        saturating_add(UNIX_EPOCH, Duration::from_secs(f.0), |t, d| {
+            t.checked_add(d)
+        })
+    }
+}
+
+/// `time`に`duration`を加算した時刻を返す。
+///
+/// 結果が表現できない場合には、`checked_add`で表現可能な最も遠い時刻に丸められる。
+pub(crate) fn saturating_add<T, F>(time: T, duration: Duration, checked_add: F) -> T
+where
+    T: Copy,
+    F: Fn(T, Duration) -> Option<T>,
+{
+    if let Some(t) = checked_add(time, duration) {
+        return t;
+    }
+
+    // NOTE: 加算に失敗した場合には、加算する時間を半分にしながら表現可能な限界まで進める
+    let mut result = time;
+    let mut step = duration / 2;
+    while step > Duration::from_secs(0) {
+        match checked_add(result, step) {
+            Some(t) => result = t,
+            None => step /= 2,
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saturating_add_works() {
+        let add = |t: SystemTime, d| t.checked_add(d);
+        let t = saturating_add(UNIX_EPOCH, Duration::from_secs(100), add);
+        assert_eq!(t, UNIX_EPOCH + Duration::from_secs(100));
+
+        // 表現できない時刻は、表現可能な最も遠い時刻に丸められる
+        let max = saturating_add(UNIX_EPOCH, Duration::from_secs(u64::MAX), add);
+        assert!(max > SystemTime::now());
+        assert_eq!(max.checked_add(Duration::new(0, 1)), None);
+        assert_eq!(saturating_add(max, Duration::from_secs(1), add), max);
+    }
+
+    #[test]
+    fn unix_timestamp_to_system_time_works() {
+        let t = SystemTime::from(UnixTimestamp(100));
+        assert_eq!(UnixTimestamp::from(t), UnixTimestamp(100));
+
+        let t = SystemTime::from(UnixTimestamp(u64::MAX));
+        assert!(t > SystemTime::now());
+    }
+}